@@ -0,0 +1,43 @@
+#![warn(clippy::all, clippy::pedantic)]
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Sets the git commit and the build date shown by `--version`, `unknown` when they can't be determined.
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map_or_else(|| String::from("unknown"), |commit| commit.trim().to_owned());
+    println!("cargo:rustc-env=GROUP_GIT_COMMIT={commit}");
+
+    // reproducible builds set the date through SOURCE_DATE_EPOCH
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs()));
+    let date = seconds.map_or_else(|| String::from("unknown"), |seconds| format_date(seconds / 86_400));
+    println!("cargo:rustc-env=GROUP_BUILD_DATE={date}");
+}
+
+/// Formats the day since the Unix epoch as `YYYY-MM-DD` in the proleptic Gregorian calendar.
+fn format_date(days: u64) -> String {
+    // days are counted from 0000-03-01, so leap days end every 400, 100 and 4 year cycle
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
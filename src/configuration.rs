@@ -1,58 +1,737 @@
+use crate::io::dataframe::{distinct_column_name, Buckets, ColumnOrder, Representative, PERCENT_COLUMN};
+use crate::io::expression::Expression;
+use crate::io::format::ColumnFormat;
+use crate::io::input::{InputAttributeSpec, InputAttributeType};
+use crate::io::transform::DISCARDED_PART;
+use crate::keys::KeyBindings;
+use regex::Regex;
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use string_error::{into_err, new_err};
+
+#[cfg(feature = "tui")]
+use crate::io::dataframe::{GroupOrder, TopGroups};
+#[cfg(feature = "tui")]
+use crate::io::input::InputFormat;
+#[cfg(feature = "tui")]
+use crate::io::serialize::KeyOrder;
+#[cfg(feature = "tui")]
+use crate::ui::{HighlightStyle, Palette};
+#[cfg(feature = "tui")]
 use structopt::StructOpt;
 
+/// Version shown by `--version`, with the git commit and the date of the build.
+#[cfg(feature = "tui")]
+const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("GROUP_GIT_COMMIT"), ", built ", env!("GROUP_BUILD_DATE"), ")");
+
+/// Command line options of the binary.
+#[cfg(feature = "tui")]
 #[derive(StructOpt)]
-#[structopt(name = "group", about = "Utility for grouping JSON input objects.")]
+#[allow(clippy::struct_excessive_bools)]
+#[structopt(name = "group", about = "Utility for grouping JSON input objects.", version = VERSION)]
 pub struct GroupOpts {
-    /// Parse input as a single JSON object (default: parse a stream of JSON objects)
+    /// Parse input as a single JSON array (default: detect an array or a stream of JSON objects)
     #[structopt(short, long)]
     pub single: bool,
 
-    /// Input file
-    #[structopt(parse(from_os_str))]
-    pub input: std::path::PathBuf,
+    /// Input format: json, yaml or csv (default: detect from the file extension)
+    #[structopt(long, possible_values = &["json", "yaml", "csv"])]
+    pub format: Option<InputFormat>,
+
+    /// Skip malformed lines when parsing a stream of JSON objects, one object per line
+    #[structopt(long)]
+    pub skip_errors: bool,
+
+    /// Read at most this many records
+    #[structopt(long)]
+    pub limit: Option<usize>,
+
+    /// Keep RFC 3339 strings as strings unless the attribute has the datetime type
+    #[structopt(long)]
+    pub no_datetime_inference: bool,
+
+    /// Accept near-JSON input with trailing commas or several top-level arrays, whose records are read in order.
+    /// The whole input is read into memory, --skip-errors skips malformed values up to the end of their line
+    #[structopt(long)]
+    pub lenient: bool,
+
+    /// Seconds to wait for the server of an input URL to connect and to send data
+    #[structopt(long, default_value = "30")]
+    pub http_timeout: u64,
+
+    /// Reload the input when any of the input files changes, bookmarks are cleared on reload
+    #[structopt(long)]
+    pub watch: bool,
+
+    /// Ask for confirmation before quitting, Ctrl-C still quits immediately
+    #[structopt(long)]
+    pub confirm_quit: bool,
+
+    /// Disable colors in the output (same as setting `NO_COLOR`)
+    #[structopt(long)]
+    pub no_color: bool,
+
+    /// Palette for coloring distinct values: rgb or cb-safe (colorblind-friendly)
+    #[structopt(long, default_value = "rgb", possible_values = &["rgb", "cb-safe"])]
+    pub palette: Palette,
+
+    /// Most distinct values of a column that get a color of their own. More values share colors,
+    /// colors of a higher limit are harder to tell apart
+    #[structopt(long, default_value = "16")]
+    pub max_colors: usize,
+
+    /// Style of the selected row: reversed, bold or underlined
+    #[structopt(long, default_value = "reversed", possible_values = &["reversed", "bold", "underlined"])]
+    pub highlight: HighlightStyle,
+
+    /// Stop at the first and the last row instead of wrapping around when moving the selection
+    #[structopt(long)]
+    pub no_wrap: bool,
+
+    /// Show a leading column with row numbers
+    #[structopt(long)]
+    pub line_numbers: bool,
+
+    /// Shade the background of every other row
+    #[structopt(long)]
+    pub zebra: bool,
+
+    /// Show sums of numeric columns and distinct value counts of other columns below the table
+    #[structopt(long)]
+    pub totals: bool,
+
+    /// Number of spaces between table columns
+    #[structopt(long, default_value = "2")]
+    pub column_spacing: u16,
+
+    /// Draw a border around the table
+    #[structopt(long)]
+    pub borders: bool,
+
+    /// Placeholder shown in the table in place of missing values
+    #[structopt(long, default_value = "∅")]
+    pub null_value: String,
+
+    /// Order of groups: first-seen or size-desc (larger groups first)
+    #[structopt(long, default_value = "first-seen", possible_values = &["first-seen", "size-desc"])]
+    pub sort_groups: GroupOrder,
+
+    /// Keep only the N largest groups
+    #[structopt(long)]
+    pub top: Option<usize>,
+
+    /// Roll the groups beyond the largest ones into an (other) group
+    #[structopt(long, requires = "top")]
+    pub other: bool,
+
+    /// Text shown in the table for true values
+    #[structopt(long, default_value = "+")]
+    pub true_value: String,
+
+    /// Text shown in the table for false values
+    #[structopt(long, default_value = "-")]
+    pub false_value: String,
+
+    /// Separator of thousands in numbers shown in the table, e.g. `,`
+    #[structopt(long)]
+    pub thousands_separator: Option<String>,
+
+    /// Number of decimal places of floats shown in the table
+    #[structopt(long)]
+    pub decimals: Option<usize>,
+
+    /// Line up decimal points of floats, every float of a column gets as many decimal places as the most precise one
+    #[structopt(long)]
+    pub align_decimals: bool,
+
+    /// Fail instead of warning when a group column has the same value or a distinct value in every row
+    #[structopt(long)]
+    pub strict: bool,
+
+    /// Order of the keys of the record shown in the card: alphabetical or original (the order of the input)
+    #[structopt(long, default_value = "alphabetical", possible_values = &["alphabetical", "original"])]
+    pub key_order: KeyOrder,
+
+    /// Maximum width of a table column in characters
+    #[structopt(long, default_value = "32")]
+    pub max_string_width: u16,
+
+    /// Write the grouped view as a Markdown table to the file, or to stdout for `-`, instead of showing it
+    #[structopt(long, parse(from_os_str))]
+    pub export_md: Option<std::path::PathBuf>,
+
+    /// Write the grouped view with the colors of the table as ANSI escape codes to the file, or to stdout for `-`
+    #[structopt(long, parse(from_os_str))]
+    pub export_ansi: Option<std::path::PathBuf>,
+
+    /// Write the grouped view with the colors of the table as an HTML table to the file, or to stdout for `-`
+    #[structopt(long, parse(from_os_str))]
+    pub export_html: Option<std::path::PathBuf>,
+
+    /// Attribute to read, can be repeated to use these attributes instead of a spec file
+    #[structopt(long = "attr", number_of_values = 1)]
+    pub attrs: Vec<String>,
+
+    /// Add a column for every scalar leaf of nested objects in the first records, the spec file is optional
+    #[structopt(long)]
+    pub flatten: bool,
+
+    /// Comma-separated columns to group by instead of the ones in the spec
+    #[structopt(long, use_delimiter = true, number_of_values = 1)]
+    pub group_by: Option<Vec<String>>,
+
+    /// Input spec file, YAML or JSON when the extension is .json, ignored if attributes are given with --attr (default: spec.yml)
+    #[structopt(long, parse(from_os_str))]
+    pub spec: Option<std::path::PathBuf>,
+
+    /// Input files or http and https URLs, gzip bodies of URLs are decompressed. Records of all inputs are merged in the given order. Without --spec,
+    /// a .yml or .yaml file after a single other input is the spec, as in `group logs.json spec.yml`
+    #[structopt(parse(from_os_str), required = true)]
+    pub input: Vec<std::path::PathBuf>,
+}
+
+#[cfg(feature = "tui")]
+impl GroupOpts {
+    /// Parses the command line, reading a spec given after a single input as the positional spec.
+    pub fn parse() -> GroupOpts {
+        GroupOpts::from_args().with_positional_spec()
+    }
+
+    /// Takes the spec from the inputs of `group INPUT SPEC`, the form used before several inputs were accepted.
+    fn with_positional_spec(mut self) -> GroupOpts {
+        let is_yaml = |path: &std::path::Path| {
+            path.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml"))
+        };
+        if self.spec.is_none() && self.input.len() == 2 && is_yaml(&self.input[1]) && !is_yaml(&self.input[0]) {
+            self.spec = self.input.pop();
+        }
+        self
+    }
+
+    fn spec_path(&self) -> &std::path::Path {
+        self.spec.as_deref().unwrap_or(std::path::Path::new("spec.yml"))
+    }
+
+    pub fn colors_enabled(&self) -> bool {
+        !self.no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+    }
 
-    /// Input spec file (default: spec.yml)
-    #[structopt(parse(from_os_str), default_value = "spec.yml")]
-    pub spec: std::path::PathBuf,
+    pub fn top_groups(&self) -> Option<TopGroups> {
+        self.top.map(|count| TopGroups { count, other: self.other })
+    }
+
+    /// Returns the spec built from `--attr` options or read from the spec file, grouped by `--group-by` columns if given.
+    /// With `--flatten` a missing spec file gives an empty spec, its grouping is chosen once the input is read,
+    /// and the spec is validated once the flattened attributes are known.
+    pub fn input_spec(&self) -> Result<InputSpec, Box<dyn std::error::Error>> {
+        let mut spec = if self.attrs.is_empty() {
+            if self.flatten && !self.spec_path().exists() {
+                InputSpec::default()
+            } else {
+                InputSpec::read_from_file(self.spec_path())?
+            }
+        } else {
+            InputSpec {
+                attrs: self.attrs.iter().map(|a| InputAttributeSpec::from(a.as_str())).collect(),
+                group_by: vec![self.attrs[0].clone()],
+                ..InputSpec::default()
+            }
+        };
+        if let Some(group_by) = &self.group_by {
+            spec.group_by.clone_from(group_by);
+        }
+        if !self.flatten {
+            spec.validate()?;
+        }
+        Ok(spec)
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Debug, Default)]
+#[serde(deny_unknown_fields)]
 pub struct InputSpec {
-    pub attrs: Vec<String>,
+    /// Attributes to read, either names or `{name, type, format}` to coerce values to the type
+    pub attrs: Vec<InputAttributeSpec>,
     pub group_by: Vec<String>,
     pub timeline_column: Option<String>,
 
     #[serde(default)]
     pub show_in_grouped: Vec<String>,
+
+    /// Values that columns shown in grouped mode show for every group, e.g. `latency: max`,
+    /// one of `first`, `last`, `min`, `max` or `distinct-count`, the first value by default
+    #[serde(default)]
+    pub represent: HashMap<String, Representative>,
+
+    /// Columns whose distinct values are counted for every group in grouped mode
+    #[serde(default)]
+    pub distinct: Vec<String>,
+
+    /// Buckets of group columns, either a width numbers are rounded to, e.g. `latency: 0.1`,
+    /// ascending edges of ranges, e.g. `latency: [100, 500]` for `<100`, `100-500` and `500+`,
+    /// or an interval datetimes are truncated to in UTC, e.g. `time: 1h`, for attributes of the datetime type
+    #[serde(default)]
+    pub buckets: HashMap<String, Buckets>,
+
+    /// Whether empty strings are grouped with missing values, for data where absent and empty mean the same
+    #[serde(default)]
+    pub coalesce_empty: bool,
+
+    /// Column whose values order the groups, e.g. `{column: host, order: desc}`, applied after `--sort-groups`
+    #[serde(alias = "order_groups_by")]
+    pub sort_groups: Option<ColumnOrder>,
+
+    /// Numeric column drawn as a bar of the group mean in grouped mode
+    pub bar: Option<BarSpec>,
+
+    /// Columns split into a column for every part, applied before derived columns
+    #[serde(default)]
+    pub split: Vec<SplitSpec>,
+
+    /// Columns captured with a regex from other columns, applied after split and before derived columns
+    #[serde(default)]
+    pub capture: Vec<CaptureSpec>,
+
+    /// Columns computed from other columns, each can use the columns defined before it
+    #[serde(default)]
+    pub derived: Vec<DerivedColumnSpec>,
+
+    /// Values whose rows are dropped after all columns are computed, they are not part of any view or count
+    #[serde(default)]
+    pub exclude: Vec<ExcludeSpec>,
+
+    /// Columns shown first in the table in this order, independent of the order of attributes
+    #[serde(default)]
+    pub display_order: Vec<String>,
+
+    /// Columns left out of the table, they can still be grouped by and used by other columns
+    #[serde(default)]
+    pub hidden: Vec<String>,
+
+    /// Display formats of columns in the table, e.g. `size: bytes` or `time: relative`, other uses keep the raw values
+    #[serde(default)]
+    pub formats: HashMap<String, ColumnFormat>,
+
+    /// Keys bound to actions, replacing the default keys of listed actions
+    #[serde(default)]
+    pub keys: KeyBindings,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct BarSpec {
+    pub column: String,
+
+    #[serde(default = "BarSpec::default_width")]
+    pub width: u16,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct DerivedColumnSpec {
+    pub name: String,
+    /// Expression of other columns, e.g. `bytes_in + bytes_out`, parsed when the spec is read
+    pub expr: Expression,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct SplitSpec {
+    pub column: String,
+    pub by: String,
+    /// Names of the part columns, `_` drops the part
+    pub into: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ExcludeSpec {
+    pub column: String,
+    /// Values converted to the type of the column the same way as values of records
+    pub values: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct CaptureSpec {
+    pub column: String,
+    pub regex: String,
+    /// Name of the captured column
+    pub into: String,
+    /// Type the captured text is coerced to
+    #[serde(rename = "as", default)]
+    pub attr_type: InputAttributeType,
+    /// Index of the capture group, the first group by default
+    #[serde(default = "CaptureSpec::default_group")]
+    pub group: usize,
+}
+
+impl CaptureSpec {
+    fn default_group() -> usize {
+        1
+    }
+
+    /// Returns the captured column as an attribute of its type.
+    pub fn attribute(&self) -> InputAttributeSpec {
+        InputAttributeSpec {
+            name: self.into.clone(),
+            attr_type: self.attr_type,
+            format: None,
+        }
+    }
+}
+
+impl BarSpec {
+    fn default_width() -> u16 {
+        16
+    }
 }
 
 impl InputSpec {
-    pub fn read_from_file(file_name: std::path::PathBuf) -> Result<InputSpec, Box<dyn std::error::Error>> {
+    /// Reads the spec from the file without validating it.
+    pub fn read_from_file(file_name: &std::path::Path) -> Result<InputSpec, Box<dyn std::error::Error>> {
         let f = std::io::BufReader::new(std::fs::File::open(file_name)?);
-        let spec: InputSpec = serde_yaml::from_reader(f)?;
+        let json = file_name.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        InputSpec::parse(f, json)
+    }
+
+    /// Parses a JSON or YAML spec and validates it.
+    pub fn from_reader<R: std::io::Read>(reader: R, json: bool) -> Result<InputSpec, Box<dyn std::error::Error>> {
+        let spec = InputSpec::parse(reader, json)?;
         spec.validate()?;
         Ok(spec)
     }
 
+    fn parse<R: std::io::Read>(reader: R, json: bool) -> Result<InputSpec, Box<dyn std::error::Error>> {
+        Ok(if json {
+            serde_json::from_reader(reader)?
+        } else {
+            serde_yaml::from_reader(reader)?
+        })
+    }
+
     pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let attr_names: HashSet<&String> = self.attrs.iter().collect();
-        if attr_names.len() != self.attrs.len() {
-            return Err(new_err("spec contains duplicates"));
+        self.validate_with_attrs(&self.attrs)
+    }
+
+    /// Validates the spec as if it listed the given attributes, such as the attributes of a flattened input.
+    pub fn validate_with_attrs(&self, attrs: &[InputAttributeSpec]) -> Result<(), Box<dyn std::error::Error>> {
+        let attr_names = self.validate_columns(attrs)?;
+        for exclude in &self.exclude {
+            if !attr_names.contains(&exclude.column) {
+                return Err(into_err(format!("missing column {} requested to exclude values of", exclude.column)));
+            }
+        }
+        for attr in attrs {
+            if attr.format.is_some() && attr.attr_type != InputAttributeType::DateTime {
+                return Err(into_err(format!("format of attribute {} is only supported for datetime type", attr.name)));
+            }
         }
 
         for attr_name in &self.group_by {
             if !attr_names.contains(attr_name) {
-                return Err(into_err(format!("missing grouping attribute {} in spec", attr_name)));
+                return Err(into_err(format!("missing grouping attribute {attr_name} in spec")));
             }
         }
         for attr_name in &self.show_in_grouped {
             if !attr_names.contains(attr_name) {
-                return Err(into_err(format!("missing attribute {} requested to show in grouped mode", attr_name)));
+                return Err(into_err(format!("missing attribute {attr_name} requested to show in grouped mode")));
+            }
+        }
+        for attr_name in self.represent.keys() {
+            if !self.show_in_grouped.contains(attr_name) {
+                return Err(into_err(format!("column {attr_name} requested to represent is not shown in grouped mode")));
+            }
+        }
+        for attr_name in &self.distinct {
+            if !attr_names.contains(attr_name) {
+                return Err(into_err(format!("missing attribute {attr_name} requested to count distinct values")));
+            }
+            if self.group_by.contains(attr_name) {
+                return Err(into_err(format!("grouping attribute {attr_name} has one distinct value in every group")));
+            }
+        }
+        for (attr_name, buckets) in &self.buckets {
+            if !attr_names.contains(attr_name) {
+                return Err(into_err(format!("missing attribute {attr_name} requested to bucket")));
+            }
+            buckets.validate().map_err(|e| into_err(format!("buckets of {attr_name}: {e}")))?;
+            let datetime = attrs
+                .iter()
+                .any(|a| a.name == *attr_name && a.attr_type == InputAttributeType::DateTime)
+                || self
+                    .capture
+                    .iter()
+                    .any(|c| c.into == *attr_name && c.attr_type == InputAttributeType::DateTime);
+            if matches!(buckets, Buckets::Interval(_)) && !datetime {
+                return Err(into_err(format!("interval buckets of {attr_name} need an attribute of the datetime type")));
+            }
+        }
+        if let Some(bar) = &self.bar {
+            if !attr_names.contains(&bar.column) {
+                return Err(into_err(format!("missing attribute {} requested to show as bar", bar.column)));
+            }
+            if bar.width == 0 {
+                return Err(new_err("bar width should be positive"));
+            }
+        }
+        // besides attributes, grouped tables show the share of rows and the distinct counts of groups
+        let is_table_column = |name: &String| {
+            attr_names.contains(name) || name == PERCENT_COLUMN || self.distinct.iter().any(|d| distinct_column_name(d) == *name)
+        };
+        if let Some(order) = &self.sort_groups {
+            // groups are ordered by the columns of the grouped table
+            let shown = self.group_by.contains(&order.column)
+                || self.show_in_grouped.contains(&order.column)
+                || order.column == PERCENT_COLUMN
+                || self.distinct.iter().any(|d| distinct_column_name(d) == order.column);
+            if !shown {
+                return Err(into_err(format!("column {} requested to order groups by is not shown in grouped mode", order.column)));
+            }
+        }
+        for name in &self.display_order {
+            if !is_table_column(name) {
+                return Err(into_err(format!("missing column {name} requested in display order")));
+            }
+        }
+        for name in &self.hidden {
+            if !is_table_column(name) {
+                return Err(into_err(format!("missing column {name} requested to hide")));
+            }
+        }
+        if !self.hidden.is_empty() && attr_names.iter().all(|name| self.hidden.contains(name)) {
+            return Err(new_err("at least one column should not be hidden"));
+        }
+        for name in self.formats.keys() {
+            if !attr_names.contains(name) {
+                return Err(into_err(format!("missing column {name} requested to format")));
             }
         }
         Ok(())
     }
+
+    /// Checks the columns computed from attributes and returns the names of all columns.
+    fn validate_columns<'a>(&'a self, attrs: &'a [InputAttributeSpec]) -> Result<HashSet<&'a String>, Box<dyn std::error::Error>> {
+        let mut attr_names: HashSet<&String> = attrs.iter().map(|a| &a.name).collect();
+        if attr_names.len() != attrs.len() {
+            return Err(new_err("spec contains duplicates"));
+        }
+        // types of columns known before records are read, split parts are strings
+        let mut column_types: HashMap<&String, InputAttributeType> = attrs.iter().map(|a| (&a.name, a.attr_type)).collect();
+        for split in &self.split {
+            column_types.extend(split.into.iter().map(|name| (name, InputAttributeType::String)));
+        }
+        column_types.extend(self.capture.iter().map(|c| (&c.into, c.attr_type)));
+        for split in &self.split {
+            if !attr_names.contains(&split.column) {
+                return Err(into_err(format!("missing attribute {} requested to split", split.column)));
+            }
+            if split.by.is_empty() {
+                return Err(into_err(format!("separator to split {} by should not be empty", split.column)));
+            }
+            for name in split.into.iter().filter(|n| *n != DISCARDED_PART) {
+                if !attr_names.insert(name) {
+                    return Err(into_err(format!("split column {name} duplicates another column")));
+                }
+            }
+        }
+        for capture in &self.capture {
+            if !attr_names.contains(&capture.column) {
+                return Err(into_err(format!("missing attribute {} requested to capture from", capture.column)));
+            }
+            let regex = Regex::new(&capture.regex).map_err(|e| into_err(format!("capture column {}: {e}", capture.into)))?;
+            if capture.group >= regex.captures_len() {
+                return Err(into_err(format!("capture column {}: regex has no group {}", capture.into, capture.group)));
+            }
+            if !attr_names.insert(&capture.into) {
+                return Err(into_err(format!("capture column {} duplicates another column", capture.into)));
+            }
+        }
+        for derived in &self.derived {
+            if let Some(missing) = derived.expr.columns().into_iter().find(|c| !attr_names.contains(c)) {
+                return Err(into_err(format!("derived column {} uses missing attribute {missing}", derived.name)));
+            }
+            if !attr_names.insert(&derived.name) {
+                return Err(into_err(format!("derived column {} duplicates another column", derived.name)));
+            }
+            let column_type = |name: &str| column_types.get(&String::from(name)).copied().unwrap_or_default();
+            let value_type = derived
+                .expr
+                .value_type(&column_type)
+                .map_err(|e| into_err(format!("derived column {}: {e}", derived.name)))?;
+            column_types.insert(&derived.name, value_type);
+        }
+        if attr_names.iter().any(|name| *name == PERCENT_COLUMN) {
+            return Err(into_err(format!("column name {PERCENT_COLUMN} is reserved for the share of rows of groups")));
+        }
+        Ok(attr_names)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::configuration::InputSpec;
+    use crate::io::dataframe::Buckets;
+    use crate::io::input::{InputAttributeSpec, InputAttributeType};
+
+    #[test]
+    fn from_reader_parses_json_and_yaml_alike() {
+        let yaml = "attrs: [status, latency]\ngroup_by: [status]\nbar:\n  column: latency\nkeys:\n  quit: [Q]\n";
+        let json = r#"{"attrs": ["status", "latency"], "group_by": ["status"], "bar": {"column": "latency"}, "keys": {"quit": ["Q"]}}"#;
+        let from_yaml = InputSpec::from_reader(yaml.as_bytes(), false).expect("YAML spec is valid");
+        let from_json = InputSpec::from_reader(json.as_bytes(), true).expect("JSON spec is valid");
+        assert_eq!(from_yaml, from_json);
+    }
+
+    #[test]
+    fn from_reader_rejects_key_bound_to_several_actions() {
+        let yaml = "attrs: [status]\ngroup_by: [status]\nkeys:\n  focus: [q]\n";
+        let message = InputSpec::from_reader(yaml.as_bytes(), false)
+            .err()
+            .map(|e| e.to_string())
+            .unwrap_or_default();
+        assert!(message.contains("key q is bound to both"), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn from_reader_parses_typed_attributes() {
+        let yaml = "attrs: [status, {name: cached, type: bool}]\ngroup_by: [status]\n";
+        let spec = InputSpec::from_reader(yaml.as_bytes(), false).expect("spec is valid");
+        let expected = vec![
+            InputAttributeSpec::from("status"),
+            InputAttributeSpec {
+                name: String::from("cached"),
+                attr_type: InputAttributeType::Bool,
+                format: None,
+            },
+        ];
+        assert_eq!(expected, spec.attrs);
+    }
+
+    #[test]
+    fn from_reader_rejects_invalid_capture() {
+        let spec = |regex: &str| format!("attrs: [name]\ngroup_by: [name]\ncapture:\n  - {{column: name, regex: '{regex}', into: id}}\n");
+        assert!(InputSpec::from_reader(spec(r"order-(\d+)").as_bytes(), false).is_ok());
+        assert!(InputSpec::from_reader(spec(r"order-\d+").as_bytes(), false).is_err());
+        assert!(InputSpec::from_reader(spec(r"order-(\d+").as_bytes(), false).is_err());
+    }
+
+    #[test]
+    fn from_reader_parses_bucket_widths_and_edges() {
+        let spec = |buckets: &str| format!("attrs: [latency]\ngroup_by: [latency]\nbuckets:\n  latency: {buckets}\n");
+        let parsed = InputSpec::from_reader(spec("0.1").as_bytes(), false).expect("spec is valid");
+        assert_eq!(Some(&Buckets::Width(0.1)), parsed.buckets.get("latency"));
+        let parsed = InputSpec::from_reader(spec("[100, 500]").as_bytes(), false).expect("spec is valid");
+        assert_eq!(Some(&Buckets::Edges(vec![100.0, 500.0])), parsed.buckets.get("latency"));
+        assert!(InputSpec::from_reader(spec("1h").as_bytes(), false).is_err());
+        let yaml = "attrs: [{name: time, type: datetime}]\ngroup_by: [time]\nbuckets:\n  time: 1h\n";
+        let parsed = InputSpec::from_reader(yaml.as_bytes(), false).expect("spec is valid");
+        assert_eq!(Some(&Buckets::Interval("1h".parse().expect("interval is valid"))), parsed.buckets.get("time"));
+        assert!(InputSpec::from_reader(spec("0").as_bytes(), false).is_err());
+        assert!(InputSpec::from_reader(spec("[]").as_bytes(), false).is_err());
+        assert!(InputSpec::from_reader(spec("[500, 100]").as_bytes(), false).is_err());
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn positional_spec_follows_single_input() {
+        use crate::configuration::GroupOpts;
+        use std::path::PathBuf;
+        use structopt::StructOpt;
+
+        let opts = GroupOpts::from_iter(["group", "logs.json", "errors.yml"]).with_positional_spec();
+        assert_eq!(Some(PathBuf::from("errors.yml")), opts.spec);
+        assert_eq!(vec![PathBuf::from("logs.json")], opts.input);
+        let opts = GroupOpts::from_iter(["group", "a.yml", "b.yml"]).with_positional_spec();
+        assert_eq!(None, opts.spec);
+        let opts = GroupOpts::from_iter(["group", "--spec", "s.yml", "logs.json", "more.yml"]).with_positional_spec();
+        assert_eq!(2, opts.input.len());
+    }
+
+    #[test]
+    fn from_reader_rejects_column_named_like_percent_column() {
+        assert!(InputSpec::from_reader("attrs: [percent]\ngroup_by: [percent]\n".as_bytes(), false).is_ok());
+        let derived = "attrs: [a]\ngroup_by: [a]\nderived:\n  - {name: '%', expr: a}\n";
+        assert!(InputSpec::from_reader(derived.as_bytes(), false).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_derived_columns_of_mismatched_types() {
+        let spec = |expr: &str| {
+            let yaml = format!(
+                "attrs: [{{name: a, type: integer}}, {{name: s, type: string}}, u]\ngroup_by: [a]\n\
+                 capture: [{{column: s, regex: '(\\d+)', into: n, type: integer}}]\n\
+                 derived:\n  - {{name: x, expr: '{expr}'}}\n  - {{name: y, expr: 'x * 2'}}\n"
+            );
+            InputSpec::from_reader(yaml.as_bytes(), false).map_err(|e| e.to_string())
+        };
+        let message = spec("a + s").err().unwrap_or_default();
+        assert!(message.ends_with("derived column x: cannot add integer and string"), "{message}");
+        let message = spec("s + \"x\"").err().unwrap_or_default();
+        assert!(message.ends_with("derived column y: cannot multiply string and integer"), "{message}");
+        assert!(spec("a + n").is_ok());
+        // values of untyped attributes are only known per record
+        assert!(spec("u + s").is_ok());
+    }
+
+    #[test]
+    fn validate_with_attrs_checks_columns_of_flattened_attributes() {
+        let yaml = "attrs: []\ngroup_by: [user.id]\nderived:\n  - {name: id, expr: user.id + 1}\n";
+        assert!(InputSpec::from_reader(yaml.as_bytes(), false).is_err());
+        let spec = InputSpec::parse(yaml.as_bytes(), false).expect("spec is well-formed");
+        assert!(spec.validate_with_attrs(&[InputAttributeSpec::from("user.id")]).is_ok());
+        assert!(spec.validate_with_attrs(&[InputAttributeSpec::from("user.name")]).is_err());
+    }
+
+    #[test]
+    fn validate_with_attrs_rejects_columns_named_like_flattened_attributes() {
+        let flattened = [InputAttributeSpec::from("path"), InputAttributeSpec::from("user.id")];
+        for computed in [
+            "split:\n  - {column: path, by: /, into: [user.id]}\n",
+            "capture:\n  - {column: path, regex: '(\\d+)', into: user.id}\n",
+            "derived:\n  - {name: user.id, expr: path}\n",
+        ] {
+            let yaml = format!("attrs: []\ngroup_by: [path]\n{computed}");
+            let spec = InputSpec::parse(yaml.as_bytes(), false).expect("spec is well-formed");
+            let message = spec
+                .validate_with_attrs(&flattened)
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default();
+            assert!(message.contains("user.id duplicates another column"), "unexpected error: {message}");
+        }
+    }
+
+    #[test]
+    fn validate_accepts_hidden_share_and_distinct_columns() {
+        let yaml = "attrs: [a, b]\ngroup_by: [a]\ndistinct: [b]\nhidden: ['%', distinct b]\n";
+        assert!(InputSpec::from_reader(yaml.as_bytes(), false).is_ok());
+        assert!(InputSpec::from_reader("attrs: [a]\ngroup_by: [a]\nhidden: [c]\n".as_bytes(), false).is_err());
+    }
+
+    #[test]
+    fn validate_orders_groups_by_columns_of_grouped_table() {
+        let spec = |column: &str| {
+            format!("attrs: [a, b, c, d]\ngroup_by: [a]\nshow_in_grouped: [b]\ndistinct: [c]\nsort_groups: {{column: '{column}'}}\n")
+        };
+        for column in ["a", "b", "%", "distinct c"] {
+            assert!(InputSpec::from_reader(spec(column).as_bytes(), false).is_ok(), "{column} is shown");
+        }
+        for column in ["c", "d", "e"] {
+            let message = InputSpec::from_reader(spec(column).as_bytes(), false)
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default();
+            assert!(message.ends_with(&format!("column {column} requested to order groups by is not shown in grouped mode")), "{message}");
+        }
+    }
+
+    #[test]
+    fn from_reader_rejects_unknown_keys() {
+        let spec = InputSpec::from_reader("attrs: [a]\ngroup_by: [a]\nsort_group: {column: a}\n".as_bytes(), false);
+        let message = spec.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("unknown field `sort_group`"), "{message}");
+    }
+
+    #[test]
+    fn validate_accepts_spec_without_attributes() {
+        // attributes of a flattened input are only known once records are read
+        assert!(InputSpec::default().validate().is_ok());
+    }
 }
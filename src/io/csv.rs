@@ -0,0 +1,73 @@
+use std::error::Error;
+use string_error::into_err;
+
+/// Reads CSV rows as objects keyed by the header row.
+///
+/// Empty cells become nulls and numeric cells become numbers, other cells are kept as strings
+/// so that the rest of the value inference is the same as for JSON input.
+pub fn read_records(reader: impl std::io::BufRead, limit: Option<usize>) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+    let mut csv_reader = ::csv::Reader::from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+
+    let mut result: Vec<serde_json::Value> = Vec::new();
+    for (i, row) in csv_reader.records().take(limit.unwrap_or(usize::MAX)).enumerate() {
+        let row = row.map_err(|e| into_err(format!("record {}: {}", i + 1, e)))?;
+        let obj: serde_json::Map<String, serde_json::Value> = headers
+            .iter()
+            .zip(row.iter())
+            .map(|(name, cell)| (String::from(name), infer_value(cell)))
+            .collect();
+        result.push(serde_json::Value::Object(obj));
+    }
+
+    Ok(result)
+}
+
+fn infer_value(cell: &str) -> serde_json::Value {
+    if cell.is_empty() {
+        serde_json::Value::Null
+    } else if let Ok(n) = cell.parse::<i64>() {
+        serde_json::Value::from(n)
+    } else if let Some(n) = cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        serde_json::Value::Number(n)
+    } else {
+        serde_json::Value::String(String::from(cell))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::csv::read_records;
+    use serde_json::json;
+
+    #[test]
+    fn read_records_infers_value_types() {
+        let input = "name,count,ratio,ts\na,10,0.5,2022-01-05T00:50:03Z\n";
+        let actual = read_records(input.as_bytes(), None).ok();
+        let expected = vec![json!({"name": "a", "count": 10, "ratio": 0.5, "ts": "2022-01-05T00:50:03Z"})];
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[test]
+    fn read_records_parses_quoted_fields() {
+        let input = "name,message\na,\"hello, \"\"world\"\"\"\n";
+        let actual = read_records(input.as_bytes(), None).ok();
+        let expected = vec![json!({"name": "a", "message": "hello, \"world\""})];
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[test]
+    fn read_records_parses_empty_cells_as_null() {
+        let input = "name,count\na,\n,3\n";
+        let actual = read_records(input.as_bytes(), None).ok();
+        let expected = vec![json!({"name": "a", "count": null}), json!({"name": null, "count": 3})];
+        assert_eq!(Some(expected), actual);
+    }
+
+    #[test]
+    fn read_records_stops_at_limit() {
+        let input = "count\n1\n2\n3\n";
+        let actual = read_records(input.as_bytes(), Some(2)).ok();
+        assert_eq!(Some(vec![json!({"count": 1}), json!({"count": 2})]), actual);
+    }
+}
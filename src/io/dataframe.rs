@@ -1,34 +1,313 @@
-use chrono::{DateTime, Utc};
+use crate::io::serialize::{to_ordered_pretty_json, KeyOrder};
+use chrono::{DateTime, TimeZone, Utc};
 use indexmap::IndexMap;
+use serde::Deserialize;
+use std::cell::OnceCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs::File;
 use std::hash::Hash;
+use std::io::{Read, Seek, SeekFrom};
 use std::ops::Index;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 use std::vec::Vec;
+use string_error::new_err;
+
+/// Name of the grouped view column with the share of all rows in each group, specs can't add a column of this name
+pub const PERCENT_COLUMN: &str = "%";
+
+/// Name of the grouped view column with the number of distinct values of the column in each group.
+pub fn distinct_column_name(column: &str) -> String {
+    format!("distinct {column}")
+}
+
+/// Key of the group rolling up the groups beyond the largest ones.
+pub const OTHER_GROUP: &str = "(other)";
+
+/// Limit of a grouped view to its largest groups.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TopGroups {
+    pub count: usize,
+    /// Whether the rest of the groups are rolled up into a last `(other)` group
+    pub other: bool,
+}
+
+/// How `group_by` groups rows and what the grouped view shows besides the group columns.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GroupOptions<'a> {
+    /// Columns that get a column with the number of their distinct values in each group
+    pub distinct_columns: &'a [String],
+    /// Buckets numbers of group columns are grouped by, groups of ranges are ordered by their edges
+    pub buckets: &'a [(String, Buckets)],
+    /// Values extra columns show for every group, the first value unless listed
+    pub representatives: &'a [(String, Representative)],
+    /// Whether empty strings are in the same group as missing values
+    pub coalesce_empty: bool,
+    /// Order of the groups, applied after limiting them to the largest groups
+    pub order: GroupOrder,
+    /// Limit to the largest groups
+    pub top: Option<TopGroups>,
+}
+
+/// Order of the groups of a grouped view.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GroupOrder {
+    /// Groups appear in the order of their first row
+    #[default]
+    FirstSeen,
+    /// Larger groups come first, groups of the same size keep the order of their first row
+    SizeDesc,
+}
+
+impl std::str::FromStr for GroupOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-seen" => Ok(GroupOrder::FirstSeen),
+            "size-desc" => Ok(GroupOrder::SizeDesc),
+            _ => Err(format!("unknown group order {s}")),
+        }
+    }
+}
+
+/// Order of groups by the values a column shows for them.
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ColumnOrder {
+    pub column: String,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Buckets numbers of a group column are grouped by.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(try_from = "BucketsSpec")]
+pub enum Buckets {
+    /// Numbers are rounded to the nearest multiple of the width, a group shows the mean of its numbers
+    Width(f64),
+    /// Numbers are grouped into the ranges between ascending edges, each range includes its lower edge
+    Edges(Vec<f64>),
+    /// Datetimes are truncated to the start of their interval, a group shows the start
+    Interval(Interval),
+}
+
+/// Buckets as written in the spec, so that a malformed interval reports its own error.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BucketsSpec {
+    Width(f64),
+    Edges(Vec<f64>),
+    Interval(String),
+}
+
+impl TryFrom<BucketsSpec> for Buckets {
+    type Error = String;
+
+    fn try_from(spec: BucketsSpec) -> Result<Self, Self::Error> {
+        Ok(match spec {
+            BucketsSpec::Width(width) => Buckets::Width(width),
+            BucketsSpec::Edges(edges) => Buckets::Edges(edges),
+            BucketsSpec::Interval(interval) => Buckets::Interval(interval.parse()?),
+        })
+    }
+}
+
+/// Value an extra column shows for a group in grouped mode, missing values are skipped except by `first` and `last`.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Representative {
+    /// Value of the first row of the group
+    First,
+    /// Value of the last row of the group
+    Last,
+    Min,
+    Max,
+    /// Number of distinct values in the group
+    DistinctCount,
+}
+
+impl Representative {
+    fn group_value(self, column: &Column, idx: &[usize]) -> ColumnValue {
+        let mut values = idx.iter().map(|i| &column[*i]);
+        let present = || idx.iter().map(|i| &column[*i]).filter(|v| **v != ColumnValue::None);
+        let value = match self {
+            Representative::First => values.next(),
+            Representative::Last => values.next_back(),
+            Representative::Min => present().reduce(|a, b| if b.compare(a) == Some(Ordering::Less) { b } else { a }),
+            Representative::Max => present().reduce(|a, b| if b.compare(a) == Some(Ordering::Greater) { b } else { a }),
+            Representative::DistinctCount => {
+                let distinct: HashSet<&ColumnValue> = present().collect();
+                return ColumnValue::Integer(i64::try_from(distinct.len()).unwrap_or(i64::MAX));
+            }
+        };
+        value.cloned().unwrap_or(ColumnValue::None)
+    }
+}
+
+/// Length of a time bucket in seconds, parsed from a number of minutes, hours or days such as `15m`, `1h` or `1d`.
+///
+/// Buckets start at multiples of the length since the Unix epoch in UTC, so days start at UTC midnight and
+/// there are no daylight saving shifts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Interval(i64);
+
+impl std::str::FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s.chars().last() {
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            Some('d') => 24 * 60 * 60,
+            _ => return Err(format!("unknown unit of interval {s}, expected m, h or d")),
+        };
+        match s[..s.len() - 1]
+            .parse::<i64>()
+            .ok()
+            .filter(|n| *n > 0)
+            .and_then(|n| n.checked_mul(unit))
+        {
+            Some(seconds) => Ok(Interval(seconds)),
+            None => Err(format!("interval {s} should start with a positive number")),
+        }
+    }
+}
+
+impl Interval {
+    fn start(self, ts: &DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = ts.timestamp();
+        Utc.timestamp(seconds - seconds.rem_euclid(self.0), 0)
+    }
+}
+
+impl Buckets {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Buckets::Width(width) if !width.is_finite() || *width <= 0.0 => Err(String::from("width should be a positive number")),
+            Buckets::Edges(edges) if edges.is_empty() || edges.iter().any(|e| !e.is_finite()) || !edges.windows(2).all(|w| w[0] < w[1]) => {
+                Err(String::from("edges should be ascending numbers"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Key of the bucket of the value, values other than numbers are their own key.
+    fn key(&self, value: &ColumnValue) -> ColumnValue {
+        match (self, value.as_f64()) {
+            // small negative numbers round to -0.0, which would be a bucket apart from 0.0
+            (Buckets::Width(width), Some(n)) => ColumnValue::Float(Float((n / width).round() * width + 0.0)),
+            (Buckets::Edges(edges), Some(n)) => ColumnValue::Integer(i64::try_from(range_index(edges, n)).unwrap_or(i64::MAX)),
+            (Buckets::Interval(interval), _) => match value {
+                ColumnValue::DateTime(ts) => ColumnValue::DateTime(interval.start(ts)),
+                _ => value.clone(),
+            },
+            _ => value.clone(),
+        }
+    }
+
+    /// Value shown for the rows of a bucket, the mean of their numbers, the label of their range or the start of their interval.
+    #[allow(clippy::cast_precision_loss)]
+    fn group_value(&self, column: &Column, idx: &[usize]) -> ColumnValue {
+        let first = &column[idx[0]];
+        match (self, first.as_f64()) {
+            (Buckets::Width(_), Some(_)) => {
+                let numbers: Vec<f64> = idx.iter().filter_map(|i| column[*i].as_f64()).collect();
+                ColumnValue::Float(Float(numbers.iter().sum::<f64>() / numbers.len() as f64))
+            }
+            (Buckets::Edges(edges), Some(n)) => ColumnValue::String(range_label(edges, range_index(edges, n))),
+            (Buckets::Interval(_), _) => self.key(first),
+            _ => first.clone(),
+        }
+    }
+}
+
+/// Index of the range of the number, 0 is the range below the first edge.
+fn range_index(edges: &[f64], n: f64) -> usize {
+    edges.partition_point(|edge| *edge <= n)
+}
+
+/// Label of the range, e.g. `100-500`, with open-ended ranges below the first edge and from the last edge.
+fn range_label(edges: &[f64], index: usize) -> String {
+    match index {
+        0 => format!("<{}", edges[0]),
+        i if i == edges.len() => format!("{}+", edges[i - 1]),
+        i => format!("{}-{}", edges[i - 1], edges[i]),
+    }
+}
+
+/// Floating point number compared and hashed by its bit pattern so that it can be a group key.
+#[derive(Debug, Clone, Copy)]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Float {}
+
+impl Hash for Float {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
 
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub enum ColumnValue {
     Integer(i64),
+    Float(Float),
     Boolean(bool),
     String(String),
     DateTime(DateTime<Utc>),
+    /// Share of the total in percent
+    Percent(Float),
     None,
 }
 
-impl std::string::ToString for ColumnValue {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for ColumnValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ColumnValue::Integer(n) => n.to_string(),
-            ColumnValue::Boolean(b) => {
-                if *b {
-                    String::from("+")
-                } else {
-                    String::from("-")
-                }
-            }
-            ColumnValue::String(s) => s.clone(),
-            ColumnValue::DateTime(d) => d.format("%H:%M:%S%.3f").to_string(),
-            ColumnValue::None => String::from("-"),
+            ColumnValue::Integer(n) => write!(f, "{n}"),
+            ColumnValue::Float(n) => write!(f, "{}", n.0),
+            ColumnValue::Boolean(b) => write!(f, "{}", if *b { "+" } else { "-" }),
+            ColumnValue::String(s) => write!(f, "{s}"),
+            ColumnValue::DateTime(d) => write!(f, "{}", d.format("%H:%M:%S%.3f")),
+            ColumnValue::Percent(p) => write!(f, "{:.1}%", p.0),
+            ColumnValue::None => Ok(()),
+        }
+    }
+}
+
+impl ColumnValue {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ColumnValue::Integer(n) => Some(*n as f64),
+            ColumnValue::Float(n) | ColumnValue::Percent(n) => Some(n.0),
+            _ => None,
+        }
+    }
+
+    /// Orders values of the same kind, numbers of any type are compared by their value.
+    pub fn compare(&self, other: &ColumnValue) -> Option<Ordering> {
+        match (self, other) {
+            (ColumnValue::String(a), ColumnValue::String(b)) => Some(a.cmp(b)),
+            (ColumnValue::Boolean(a), ColumnValue::Boolean(b)) => Some(a.cmp(b)),
+            (ColumnValue::DateTime(a), ColumnValue::DateTime(b)) => Some(a.cmp(b)),
+            (a, b) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
         }
     }
 }
@@ -43,6 +322,11 @@ impl Column {
     pub fn unique(&self) -> HashSet<&ColumnValue> {
         self.values.iter().collect::<HashSet<_>>()
     }
+
+    pub fn is_numeric(&self) -> bool {
+        let mut values = self.values.iter().filter(|v| **v != ColumnValue::None).peekable();
+        values.peek().is_some() && values.all(|v| matches!(v, ColumnValue::Integer(_) | ColumnValue::Float(_) | ColumnValue::Percent(_)))
+    }
 }
 
 impl Index<usize> for Column {
@@ -54,36 +338,174 @@ impl Index<usize> for Column {
 
 pub trait DataFrame {
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn column_names(&self) -> Vec<&String>;
-    fn row(&self, index: usize) -> Vec<ColumnValue>;
     fn raw(&self, index: usize) -> &String;
     fn column(&self, key: &str) -> &Column;
     fn get(&self, key: (&String, usize)) -> &ColumnValue;
+    /// Values of the row paired with the names of their columns, in the order of `column_names`
+    fn named_row(&self, index: usize) -> Vec<(&String, &ColumnValue)> {
+        self.column_names()
+            .into_iter()
+            .map(|name| (name, self.get((name, index))))
+            .collect()
+    }
+    /// Columns whose values identify the rows
+    fn key_columns(&self) -> &[String] {
+        &[]
+    }
+    /// Number of source rows represented by the row
+    fn group_size(&self, _index: usize) -> usize {
+        1
+    }
+    /// Index of the row in the source dataframe, the first row of a group for grouped views
+    fn source_index(&self, index: usize) -> usize {
+        index
+    }
+    /// Source rows of the row if it rolls up rows with different keys
+    fn rolled_up_rows(&self, _index: usize) -> Option<&Vec<usize>> {
+        None
+    }
 }
 
-#[derive(PartialEq, Debug)]
+/// Record of a dataframe row, shown in the card.
+#[derive(Debug, PartialEq)]
+pub enum RawRecord {
+    /// Record kept in memory
+    Value(serde_json::Value),
+    /// Byte range of the record in its file, the record is read again when shown
+    Offset { file: Arc<ScannedFile>, range: Range<u64> },
+}
+
+/// File records are read again from, with its length and modification time when it was scanned.
+#[derive(Debug, PartialEq)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl ScannedFile {
+    pub fn new(path: &Path) -> std::io::Result<ScannedFile> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(ScannedFile {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// Opens the file, failing if it changed since it was scanned so that ranges may point at other records.
+    fn open(&self) -> Result<File, Box<dyn std::error::Error>> {
+        let file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        if metadata.len() != self.len || metadata.modified().ok() != self.modified {
+            return Err(new_err("the file changed since it was read, reload it to show the record"));
+        }
+        Ok(file)
+    }
+}
+
+impl RawRecord {
+    fn to_pretty_json(&self, order: KeyOrder) -> String {
+        match self {
+            RawRecord::Value(value) => to_ordered_pretty_json(value, order).expect("JSON value is always serializable"),
+            RawRecord::Offset { file, range } => read_record(file, range.clone(), order)
+                .unwrap_or_else(|e| format!("failed to read record from {}: {e}", file.path.display())),
+        }
+    }
+}
+
+fn read_record(file: &ScannedFile, range: Range<u64>, order: KeyOrder) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = file.open()?;
+    file.seek(SeekFrom::Start(range.start))?;
+    let mut buffer = Vec::new();
+    file.take(range.end - range.start).read_to_end(&mut buffer)?;
+    let value: serde_json::Value = serde_json::from_slice(&buffer)?;
+    Ok(to_ordered_pretty_json(&value, order)?)
+}
+
+#[derive(Debug)]
 pub struct MaterializedDataFrame {
-    pub columns: IndexMap<String, Column>,
-    raw_values: Vec<String>,
+    /// Private so that every change of values goes through methods resetting `group_index`
+    columns: IndexMap<String, Column>,
+    records: Vec<RawRecord>,
+    raw_values: Vec<OnceCell<String>>,
+    /// Order of the keys of raw records
+    key_order: KeyOrder,
+    /// Rows of every group of the first `group_by`, used to look up rows by their group instead of scanning
+    group_index: OnceCell<GroupIndex>,
+}
+
+/// Rows of every distinct tuple of values of the columns, in the order of their first row.
+#[derive(Debug)]
+struct GroupIndex {
+    columns: Vec<String>,
+    /// Buckets numbers of each column are grouped by
+    buckets: Vec<Option<Buckets>>,
+    /// Whether empty strings are grouped with missing values
+    coalesce_empty: bool,
+    rows: IndexMap<Vec<ColumnValue>, Vec<usize>>,
+}
+
+impl GroupIndex {
+    fn new(df: &MaterializedDataFrame, columns: &[String], buckets: &[Option<&Buckets>], coalesce_empty: bool) -> GroupIndex {
+        let mut rows: IndexMap<Vec<ColumnValue>, Vec<usize>> = IndexMap::new();
+        for i in 0..df.len() {
+            let row: Vec<ColumnValue> = columns
+                .iter()
+                .zip(buckets)
+                .map(|(name, bucket)| group_key(&df[name][i], *bucket, coalesce_empty))
+                .collect();
+            if let Some(group) = rows.get_mut(&row) {
+                group.push(i);
+            } else {
+                rows.insert(row, vec![i]);
+            }
+        }
+        GroupIndex {
+            columns: columns.to_vec(),
+            buckets: buckets.iter().map(|bucket| bucket.cloned()).collect(),
+            coalesce_empty,
+            rows,
+        }
+    }
+}
+
+fn retain_rows<T>(values: &mut Vec<T>, keep: &[bool]) {
+    let mut keep = keep.iter();
+    values.retain(|_| keep.next().copied().unwrap_or(true));
+}
+
+/// Value the rows of a group share, the bucket of the value and no value for empty strings if they are coalesced.
+fn group_key(value: &ColumnValue, bucket: Option<&Buckets>, coalesce_empty: bool) -> ColumnValue {
+    match value {
+        ColumnValue::String(s) if coalesce_empty && s.is_empty() => ColumnValue::None,
+        _ => bucket.map_or_else(|| value.clone(), |bucket| bucket.key(value)),
+    }
+}
+
+impl PartialEq for MaterializedDataFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.columns == other.columns && self.records == other.records
+    }
 }
 
 impl MaterializedDataFrame {}
 
 impl DataFrame for MaterializedDataFrame {
     fn len(&self) -> usize {
-        self.columns[0].values.len()
+        self.records.len()
     }
 
     fn column_names(&self) -> Vec<&String> {
         self.columns.keys().collect()
     }
 
-    fn row(&self, index: usize) -> Vec<ColumnValue> {
-        self.columns.values().map(|c| c[index].clone()).collect()
-    }
-
     fn raw(&self, index: usize) -> &String {
-        &self.raw_values[index]
+        self.raw_values[index].get_or_init(|| self.records[index].to_pretty_json(self.key_order))
     }
 
     fn column(&self, key: &str) -> &Column {
@@ -112,47 +534,329 @@ impl Index<(&String, usize)> for MaterializedDataFrame {
 }
 
 impl MaterializedDataFrame {
-    pub fn new(columns: IndexMap<String, Column>, raw_values: Vec<String>) -> MaterializedDataFrame {
-        assert!(!columns.is_empty(), "data should have at least one column");
-        let row_counts: Vec<usize> = columns.values().map(|c| c.values.len()).collect();
-        assert!(!row_counts.is_empty(), "data should have at least one row");
-        assert!(row_counts.iter().min() == row_counts.iter().max(), "columns have different number of rows");
-
-        MaterializedDataFrame { columns, raw_values }
-    }
-
-    pub fn filter(&self, column_filters: &HashMap<String, ColumnValue>) -> DataFrameFilterView {
-        let idx: Vec<usize> = (0..self.len())
-            .filter(|i| {
-                for c in self.columns.values() {
-                    if let Some(expected_value) = column_filters.get(&c.name) {
-                        if expected_value != &c[*i] {
-                            return false;
-                        }
-                    }
-                }
-                true
+    /// Creates a dataframe of records, every column should have a value for each record.
+    pub fn new(columns: IndexMap<String, Column>, records: Vec<serde_json::Value>) -> MaterializedDataFrame {
+        MaterializedDataFrame::from_raw_records(columns, records.into_iter().map(RawRecord::Value).collect())
+    }
+
+    /// Creates a dataframe of records kept in memory or in files, every column should have a value for each record.
+    pub fn from_raw_records(columns: IndexMap<String, Column>, records: Vec<RawRecord>) -> MaterializedDataFrame {
+        assert!(columns.values().all(|c| c.values.len() == records.len()), "columns should have a value for each record");
+
+        let raw_values = records.iter().map(|_| OnceCell::new()).collect();
+        MaterializedDataFrame {
+            columns,
+            records,
+            raw_values,
+            key_order: KeyOrder::default(),
+            group_index: OnceCell::new(),
+        }
+    }
+
+    /// Sets the order of the keys of raw records, the records are rendered again when they are shown next.
+    pub fn set_key_order(&mut self, order: KeyOrder) {
+        self.key_order = order;
+        self.raw_values = self.records.iter().map(|_| OnceCell::new()).collect();
+    }
+
+    /// Appends rows of the dataframe, both dataframes should have the same columns.
+    pub fn append(&mut self, other: MaterializedDataFrame) {
+        assert!(self.columns.keys().eq(other.columns.keys()), "appended dataframe should have the same columns");
+
+        for (column, other_column) in self.columns.values_mut().zip(other.columns.into_iter().map(|(_, c)| c)) {
+            column.values.extend(other_column.values);
+        }
+        self.records.extend(other.records);
+        self.raw_values.extend(other.raw_values);
+        self.group_index = OnceCell::new();
+    }
+
+    /// Adds the column after the others, fails if a column of the same name exists.
+    pub fn add_column(&mut self, column: Column) -> Result<(), String> {
+        assert_eq!(self.len(), column.values.len(), "column should have a value for each record");
+
+        if self.columns.contains_key(&column.name) {
+            return Err(format!("column {} already exists", column.name));
+        }
+        self.columns.insert(column.name.clone(), column);
+        self.group_index = OnceCell::new();
+        Ok(())
+    }
+
+    /// Returns the column of the name, if any.
+    pub fn get_column(&self, name: &str) -> Option<&Column> {
+        self.columns.get(name)
+    }
+
+    /// Drops the rows indexed by group, the next grouping builds the index for its own columns.
+    pub fn reset_group_index(&mut self) {
+        self.group_index = OnceCell::new();
+    }
+
+    /// Number of records kept in memory, the others are read from their files when shown.
+    pub fn records_in_memory(&self) -> usize {
+        self.records.iter().filter(|r| matches!(r, RawRecord::Value(_))).count()
+    }
+
+    /// Keeps rows where every filtered column has the given value.
+    ///
+    /// Filters on all the columns of the first `group_by` only check the rows of the group, others scan every row.
+    pub fn filter(&self, column_filters: &HashMap<&str, &ColumnValue>) -> DataFrameFilterView<'_> {
+        let Some(group) = self.indexed_rows(column_filters) else {
+            return self.filter_rows(|i| self.matches(column_filters, i));
+        };
+        let idx = group.iter().copied().filter(|i| self.matches(column_filters, *i)).collect();
+
+        DataFrameFilterView { source: self, idx }
+    }
+
+    /// Rows of the indexed group matching the filter, if it filters every indexed column.
+    fn indexed_rows(&self, column_filters: &HashMap<&str, &ColumnValue>) -> Option<&[usize]> {
+        let index = self.group_index.get()?;
+        // the index is keyed by owned values, so the filtered values are cloned once for the lookup
+        let key: Vec<ColumnValue> = index
+            .columns
+            .iter()
+            .zip(&index.buckets)
+            .map(|(c, bucket)| {
+                column_filters
+                    .get(c.as_str())
+                    .map(|v| group_key(v, bucket.as_ref(), index.coalesce_empty))
             })
+            .collect::<Option<_>>()?;
+        Some(index.rows.get(&key).map_or(&[], Vec::as_slice))
+    }
+
+    /// Keeps rows where at least one filtered column has a different value, the complement of `filter`.
+    pub fn exclude(&self, column_filters: &HashMap<&str, &ColumnValue>) -> DataFrameFilterView<'_> {
+        self.filter_rows(|i| !self.matches(column_filters, i))
+    }
+
+    /// Keeps rows where every filtered column has one of the listed values.
+    pub fn filter_in(&self, column_filters: &HashMap<String, Vec<ColumnValue>>) -> DataFrameFilterView<'_> {
+        self.filter_rows(|i| self.listed(column_filters, i).all(|listed| listed))
+    }
+
+    /// Removes rows where any filtered column has one of the listed values, the complement of `filter_in` as a filter at load time.
+    pub fn remove_listed(&mut self, column_filters: &HashMap<String, Vec<ColumnValue>>) {
+        let keep: Vec<bool> = (0..self.len())
+            .map(|i| !self.listed(column_filters, i).any(|listed| listed))
             .collect();
+        for column in self.columns.values_mut() {
+            retain_rows(&mut column.values, &keep);
+        }
+        retain_rows(&mut self.records, &keep);
+        retain_rows(&mut self.raw_values, &keep);
+        self.group_index = OnceCell::new();
+    }
+
+    /// Whether the value of the row is listed, for every filtered column.
+    fn listed<'b>(&'b self, column_filters: &'b HashMap<String, Vec<ColumnValue>>, index: usize) -> impl Iterator<Item = bool> + 'b {
+        self.columns
+            .values()
+            .filter_map(move |c| column_filters.get(&c.name).map(|values| values.contains(&c[index])))
+    }
+
+    /// Keeps rows with the given indices, in their source order.
+    pub fn select(&self, indices: &HashSet<usize>) -> DataFrameFilterView<'_> {
+        self.filter_rows(|i| indices.contains(&i))
+    }
+
+    fn matches(&self, column_filters: &HashMap<&str, &ColumnValue>, index: usize) -> bool {
+        self.columns.values().all(|c| {
+            column_filters
+                .get(c.name.as_str())
+                .is_none_or(|expected_value| *expected_value == &c[index])
+        })
+    }
+
+    fn filter_rows(&self, keep: impl Fn(usize) -> bool) -> DataFrameFilterView<'_> {
+        let idx: Vec<usize> = (0..self.len()).filter(|i| keep(*i)).collect();
 
         DataFrameFilterView { source: self, idx }
     }
 
-    pub fn group_by<'a>(&'a self, columns: &'a [String], extra_columns: &'a [String]) -> DataFrameGroupView {
-        let mut row_indices: indexmap::IndexMap<Vec<ColumnValue>, Vec<usize>> = indexmap::IndexMap::new();
-        for i in 0..self.len() {
-            let row: Vec<ColumnValue> = columns.iter().map(|name| self[name][i].clone()).collect();
-            if let Some(group) = row_indices.get_mut(&row) {
-                group.push(i);
-            } else {
-                row_indices.insert(row, vec![i]);
+    /// Rows of every group in the order of their first row, or of their ranges for columns with bucket edges.
+    fn group_rows(&self, columns: &[String], column_buckets: &[Option<&Buckets>], coalesce_empty: bool) -> Vec<Vec<usize>> {
+        let index = self
+            .group_index
+            .get_or_init(|| GroupIndex::new(self, columns, column_buckets, coalesce_empty));
+        let mut group_idx: Vec<Vec<usize>> = if index.columns == columns
+            && index.buckets.iter().map(Option::as_ref).eq(column_buckets.iter().copied())
+            && index.coalesce_empty == coalesce_empty
+        {
+            index.rows.values().cloned().collect()
+        } else {
+            GroupIndex::new(self, columns, column_buckets, coalesce_empty)
+                .rows
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect()
+        };
+        // ranges and intervals come in ascending order, rows without a number or datetime after them
+        let ordered: Vec<(&Column, &Buckets)> = columns
+            .iter()
+            .zip(column_buckets)
+            .filter_map(|(name, bucket)| match bucket {
+                Some(bucket @ (Buckets::Edges(_) | Buckets::Interval(_))) => Some((&self[name], *bucket)),
+                _ => None,
+            })
+            .collect();
+        if !ordered.is_empty() {
+            group_idx.sort_by_cached_key(|idx| {
+                let first = idx[0];
+                ordered
+                    .iter()
+                    .map(|(column, bucket)| match (bucket, &column[first]) {
+                        (Buckets::Edges(edges), value) => value
+                            .as_f64()
+                            .map_or(i64::MAX, |n| i64::try_from(range_index(edges, n)).unwrap_or(i64::MAX)),
+                        (_, ColumnValue::DateTime(ts)) => ts.timestamp(),
+                        _ => i64::MAX,
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+        group_idx
+    }
+
+    /// Describes group columns that make grouping pointless, a column with a single value gives one group
+    /// and a column with a distinct value in every row gives a group for every row.
+    ///
+    /// Values are compared by the groups they go to, so values of the same bucket count as one value.
+    pub fn group_column_warnings(&self, columns: &[String], options: &GroupOptions) -> Vec<String> {
+        if self.len() < 2 {
+            return Vec::new();
+        }
+        columns
+            .iter()
+            .filter_map(|name| {
+                let bucket = options.buckets.iter().find(|(b, _)| b == name).map(|(_, bucket)| bucket);
+                let distinct = self
+                    .columns
+                    .get(name)?
+                    .values
+                    .iter()
+                    .map(|v| group_key(v, bucket, options.coalesce_empty))
+                    .filter(|v| *v != ColumnValue::None)
+                    .collect::<HashSet<_>>()
+                    .len();
+                if distinct == 1 {
+                    Some(format!("group column {name} has the same value in every row"))
+                } else if distinct == self.len() {
+                    Some(format!("group column {name} has a distinct value in every row"))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Groups rows by the columns, extra columns show a value of every group as chosen by the options.
+    ///
+    /// The groups of the first call are kept to speed up `filter` until the dataframe changes or `reset_group_index`.
+    pub fn group_by<'a>(&'a self, columns: &'a [String], extra_columns: &'a [String], options: &GroupOptions) -> DataFrameGroupView<'a> {
+        let GroupOptions {
+            distinct_columns,
+            buckets,
+            representatives,
+            coalesce_empty,
+            order,
+            top,
+        } = *options;
+        let column_buckets: Vec<Option<&Buckets>> = columns
+            .iter()
+            .map(|c| buckets.iter().find(|(name, _)| name == c).map(|(_, bucket)| bucket))
+            .collect();
+        let mut group_idx = self.group_rows(columns, &column_buckets, coalesce_empty);
+        let mut other = None;
+        if let Some(top) = top.filter(|top| group_idx.len() > top.count) {
+            let mut by_size: Vec<usize> = (0..group_idx.len()).collect();
+            by_size.sort_by_key(|i| std::cmp::Reverse(group_idx[*i].len()));
+            let largest: HashSet<usize> = by_size.into_iter().take(top.count).collect();
+            let (kept, rest): (Vec<_>, Vec<_>) = group_idx.into_iter().enumerate().partition(|(i, _)| largest.contains(i));
+            group_idx = kept.into_iter().map(|(_, idx)| idx).collect();
+            if top.other {
+                let mut rest: Vec<usize> = rest.into_iter().flat_map(|(_, idx)| idx).collect();
+                rest.sort_unstable();
+                other = Some(rest);
             }
         }
+        if order == GroupOrder::SizeDesc {
+            // the sort is stable, so groups of the same size stay in the order of their first row
+            group_idx.sort_by_key(|idx| std::cmp::Reverse(idx.len()));
+        }
+        let has_other = other.is_some();
+        group_idx.extend(other);
+        // missing values are not counted as a distinct value
+        let mut aggregates: IndexMap<String, Column> = distinct_columns
+            .iter()
+            .map(|name| {
+                let column = &self[name];
+                let values = group_idx
+                    .iter()
+                    .map(|idx| {
+                        let distinct: HashSet<&ColumnValue> =
+                            idx.iter().map(|i| &column[*i]).filter(|v| **v != ColumnValue::None).collect();
+                        ColumnValue::Integer(i64::try_from(distinct.len()).unwrap_or(i64::MAX))
+                    })
+                    .collect();
+                let name = distinct_column_name(name);
+                (name.clone(), Column { name, values })
+            })
+            .collect();
+        #[allow(clippy::cast_precision_loss)]
+        let percent = Column {
+            name: String::from(PERCENT_COLUMN),
+            values: group_idx
+                .iter()
+                .map(|idx| ColumnValue::Percent(Float(100.0 * idx.len() as f64 / self.len() as f64)))
+                .collect(),
+        };
+        aggregates.insert(percent.name.clone(), percent);
+        let bucket_values = columns
+            .iter()
+            .zip(&column_buckets)
+            .filter_map(|(name, bucket)| bucket.map(|bucket| (name, bucket)))
+            .map(|(name, bucket)| {
+                let column = &self[name];
+                let values = group_idx.iter().map(|idx| bucket.group_value(column, idx)).collect();
+                (
+                    name.clone(),
+                    Column {
+                        name: name.clone(),
+                        values,
+                    },
+                )
+            })
+            .collect();
+        let representative_values = representatives
+            .iter()
+            .filter(|(name, representative)| extra_columns.contains(name) && *representative != Representative::First)
+            .map(|(name, representative)| {
+                let column = &self[name];
+                let values = group_idx.iter().map(|idx| representative.group_value(column, idx)).collect();
+                (
+                    name.clone(),
+                    Column {
+                        name: name.clone(),
+                        values,
+                    },
+                )
+            })
+            .collect();
 
         DataFrameGroupView {
-            group_idx: row_indices.into_iter().map(|(_, v)| v).collect(),
+            group_idx,
+            has_other,
+            other_key: ColumnValue::String(String::from(OTHER_GROUP)),
+            missing: ColumnValue::None,
             group_columns: columns,
             extra_columns,
+            aggregates,
+            bucket_values,
+            coalesce_empty,
+            representative_values,
             source: self,
         }
     }
@@ -163,7 +867,7 @@ pub struct DataFrameFilterView<'a> {
     idx: Vec<usize>,
 }
 
-impl<'a> DataFrame for DataFrameFilterView<'a> {
+impl DataFrame for DataFrameFilterView<'_> {
     fn len(&self) -> usize {
         self.idx.len()
     }
@@ -172,10 +876,6 @@ impl<'a> DataFrame for DataFrameFilterView<'a> {
         self.source.column_names()
     }
 
-    fn row(&self, index: usize) -> Vec<ColumnValue> {
-        self.source.columns.values().map(|c| c[self.idx[index]].clone()).collect()
-    }
-
     fn raw(&self, index: usize) -> &String {
         self.source.raw(self.idx[index])
     }
@@ -187,9 +887,13 @@ impl<'a> DataFrame for DataFrameFilterView<'a> {
     fn get(&self, key: (&String, usize)) -> &ColumnValue {
         &self[key]
     }
+
+    fn source_index(&self, index: usize) -> usize {
+        self.idx[index]
+    }
 }
 
-impl<'a> Index<&String> for DataFrameFilterView<'a> {
+impl Index<&String> for DataFrameFilterView<'_> {
     type Output = Column;
 
     fn index(&self, key: &String) -> &Column {
@@ -197,7 +901,7 @@ impl<'a> Index<&String> for DataFrameFilterView<'a> {
     }
 }
 
-impl<'a> Index<(&String, usize)> for DataFrameFilterView<'a> {
+impl Index<(&String, usize)> for DataFrameFilterView<'_> {
     type Output = ColumnValue;
 
     fn index(&self, key: (&String, usize)) -> &ColumnValue {
@@ -210,19 +914,33 @@ pub struct DataFrameGroupView<'a> {
     group_columns: &'a [String],
     extra_columns: &'a [String],
     group_idx: Vec<Vec<usize>>,
+    /// Whether the last group rolls up the groups beyond the largest ones
+    has_other: bool,
+    /// Value of the group columns of the `(other)` group
+    other_key: ColumnValue,
+    /// Value of the extra columns of the `(other)` group
+    missing: ColumnValue,
+    /// Columns computed over every group, shown after the source columns
+    aggregates: IndexMap<String, Column>,
+    /// Values of bucketed group columns shown for every group, the mean or the range of the bucket
+    bucket_values: HashMap<String, Column>,
+    /// Whether groups contain both empty strings and missing values
+    coalesce_empty: bool,
+    /// Values of extra columns represented by other than their first value
+    representative_values: HashMap<String, Column>,
 }
 
-impl<'a> DataFrame for DataFrameGroupView<'a> {
+impl DataFrame for DataFrameGroupView<'_> {
     fn len(&self) -> usize {
         self.group_idx.len()
     }
 
     fn column_names(&self) -> Vec<&String> {
-        self.group_columns.iter().chain(self.extra_columns.iter()).collect()
-    }
-
-    fn row(&self, index: usize) -> Vec<ColumnValue> {
-        self.source.columns.values().map(|c| c[self.group_idx[index][0]].clone()).collect()
+        self.group_columns
+            .iter()
+            .chain(self.extra_columns.iter())
+            .chain(self.aggregates.keys())
+            .collect()
     }
 
     fn raw(&self, index: usize) -> &String {
@@ -230,32 +948,612 @@ impl<'a> DataFrame for DataFrameGroupView<'a> {
     }
 
     fn column(&self, key: &str) -> &Column {
-        self.source.column(key)
+        self.aggregates
+            .get(key)
+            .or_else(|| self.bucket_values.get(key))
+            .or_else(|| self.representative_values.get(key))
+            .unwrap_or_else(|| self.source.column(key))
     }
 
     fn get(&self, key: (&String, usize)) -> &ColumnValue {
         &self[key]
     }
+
+    fn key_columns(&self) -> &[String] {
+        self.group_columns
+    }
+
+    fn group_size(&self, index: usize) -> usize {
+        self.group_idx[index].len()
+    }
+
+    fn source_index(&self, index: usize) -> usize {
+        self.group_idx[index][0]
+    }
+
+    fn rolled_up_rows(&self, index: usize) -> Option<&Vec<usize>> {
+        // rows of a bucket, of coalesced values or of represented values have different values, so they can't be filtered by the shown value
+        (self.is_other(index) || !self.bucket_values.is_empty() || !self.representative_values.is_empty() || self.coalesce_empty)
+            .then(|| &self.group_idx[index])
+    }
 }
 
-impl<'a> Index<&String> for DataFrameGroupView<'a> {
+impl Index<&String> for DataFrameGroupView<'_> {
     type Output = Column;
 
     fn index(&self, key: &String) -> &Column {
-        &self.source[key]
+        self.column(key)
     }
 }
 
-impl<'a> Index<(&String, usize)> for DataFrameGroupView<'a> {
+impl Index<(&String, usize)> for DataFrameGroupView<'_> {
     type Output = ColumnValue;
 
     fn index(&self, key: (&String, usize)) -> &ColumnValue {
-        &self.source[key.0][self.group_idx[key.1][0]]
+        match self.aggregates.get(key.0) {
+            Some(column) => &column[key.1],
+            None if self.is_other(key.1) && self.group_columns.contains(key.0) => &self.other_key,
+            None if self.is_other(key.1) => &self.missing,
+            None => match self.bucket_values.get(key.0).or_else(|| self.representative_values.get(key.0)) {
+                Some(column) => &column[key.1],
+                None => &self.source[key.0][self.group_idx[key.1][0]],
+            },
+        }
     }
 }
 
-impl<'a> DataFrameGroupView<'a> {
+impl DataFrameGroupView<'_> {
+    /// Orders the groups by the values shown in the column, missing values come last and the `(other)` group stays last.
+    ///
+    /// The sort is stable, so groups with the same value keep their order. A column not in the view leaves the order unchanged.
+    pub fn sort_by_column(&mut self, sort: &ColumnOrder) {
+        if !self.column_names().contains(&&sort.column) {
+            return;
+        }
+        let count = self.group_idx.len() - usize::from(self.has_other);
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by(|a, b| match (&self[(&sort.column, *a)], &self[(&sort.column, *b)]) {
+            (ColumnValue::None, ColumnValue::None) => Ordering::Equal,
+            (ColumnValue::None, _) => Ordering::Greater,
+            (_, ColumnValue::None) => Ordering::Less,
+            (a, b) => {
+                let ordering = a.compare(b).unwrap_or(Ordering::Equal);
+                if sort.order == SortOrder::Desc {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+        });
+        order.extend(count..self.group_idx.len());
+
+        let permute = |values: &mut Vec<ColumnValue>| *values = order.iter().map(|i| values[*i].clone()).collect();
+        for column in self
+            .aggregates
+            .values_mut()
+            .chain(self.bucket_values.values_mut())
+            .chain(self.representative_values.values_mut())
+        {
+            permute(&mut column.values);
+        }
+        self.group_idx = order.iter().map(|i| self.group_idx[*i].clone()).collect();
+    }
+
     pub fn group_indices(&self, index: usize) -> &Vec<usize> {
         &self.group_idx[index]
     }
+
+    fn is_other(&self, index: usize) -> bool {
+        self.has_other && index + 1 == self.group_idx.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{
+        distinct_column_name, Buckets, Column, ColumnOrder, ColumnValue, DataFrame, Float, GroupOptions, GroupOrder, Interval,
+        MaterializedDataFrame, Representative, SortOrder, TopGroups, OTHER_GROUP, PERCENT_COLUMN,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    #[test]
+    fn group_by_computes_size_and_share_of_each_group() {
+        let values: Vec<ColumnValue> = (0..10).map(|i| ColumnValue::Boolean(i < 7)).collect();
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let column = Column {
+            name: String::from("error"),
+            values,
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let group_columns = [String::from("error")];
+        let grouped = df.group_by(&group_columns, &[], &GroupOptions::default());
+
+        let percent = String::from(PERCENT_COLUMN);
+        assert_eq!(&ColumnValue::Percent(Float(70.0)), grouped.get((&percent, 0)));
+        assert_eq!(&ColumnValue::Percent(Float(30.0)), grouped.get((&percent, 1)));
+        assert_eq!("70.0%", grouped.get((&percent, 0)).to_string());
+        assert_eq!(7, grouped.group_size(0));
+        assert_eq!(3, grouped.group_size(1));
+        assert_eq!(1, df.group_size(0));
+        assert_eq!(
+            vec![
+                (&group_columns[0], &ColumnValue::Boolean(false)),
+                (&percent, &ColumnValue::Percent(Float(30.0)))
+            ],
+            grouped.named_row(1)
+        );
+    }
+
+    #[test]
+    fn dataframe_without_rows_is_empty() {
+        let column = Column {
+            name: String::from("level"),
+            values: vec![],
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), vec![]);
+        assert_eq!(0, df.len());
+        assert_eq!(0, df.filter(&HashMap::new()).len());
+        let group_columns = [String::from("level")];
+        assert_eq!(0, df.group_by(&group_columns, &[], &GroupOptions::default()).len());
+    }
+
+    #[test]
+    fn group_by_coalesces_empty_strings_with_missing_values_if_asked() {
+        let values = vec![
+            ColumnValue::String(String::from("a")),
+            ColumnValue::String(String::new()),
+            ColumnValue::None,
+            ColumnValue::String(String::new()),
+        ];
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let column = Column {
+            name: String::from("user"),
+            values,
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let group_columns = [String::from("user")];
+
+        let separate = df.group_by(&group_columns, &[], &GroupOptions::default());
+        assert_eq!(3, separate.len());
+        assert_eq!(&vec![1, 3], separate.group_indices(1));
+        assert_eq!(None, separate.rolled_up_rows(1));
+
+        let coalesced = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                coalesce_empty: true,
+                ..GroupOptions::default()
+            },
+        );
+        assert_eq!(2, coalesced.len());
+        assert_eq!(&vec![1, 2, 3], coalesced.group_indices(1));
+        // the group has both values, so its rows are selected by index
+        assert_eq!(Some(&vec![1, 2, 3]), coalesced.rolled_up_rows(1));
+    }
+
+    #[test]
+    fn group_by_orders_groups_by_size() {
+        let values: Vec<ColumnValue> = ["a", "b", "c", "b", "c", "d", "c"]
+            .iter()
+            .map(|v| ColumnValue::String(String::from(*v)))
+            .collect();
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let column = Column {
+            name: String::from("level"),
+            values,
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let group_columns = [String::from("level")];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                order: GroupOrder::SizeDesc,
+                ..GroupOptions::default()
+            },
+        );
+
+        let keys: Vec<String> = (0..grouped.len())
+            .map(|i| grouped.get((&group_columns[0], i)).to_string())
+            .collect();
+        assert_eq!(vec!["c", "b", "a", "d"], keys);
+        assert_eq!(vec![3, 2, 1, 1], (0..grouped.len()).map(|i| grouped.group_size(i)).collect::<Vec<_>>());
+        let percent = String::from(PERCENT_COLUMN);
+        assert_eq!("42.9%", grouped.get((&percent, 0)).to_string());
+    }
+
+    #[test]
+    fn group_by_rolls_up_groups_beyond_top_into_other() {
+        let values: Vec<ColumnValue> = ["a", "b", "c", "b", "c", "d", "c", "e"]
+            .iter()
+            .map(|v| ColumnValue::String(String::from(*v)))
+            .collect();
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let column = Column {
+            name: String::from("level"),
+            values,
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let group_columns = [String::from("level")];
+        let top = TopGroups { count: 2, other: true };
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                top: Some(top),
+                ..GroupOptions::default()
+            },
+        );
+
+        let keys: Vec<String> = (0..grouped.len())
+            .map(|i| grouped.get((&group_columns[0], i)).to_string())
+            .collect();
+        assert_eq!(vec!["b", "c", OTHER_GROUP], keys);
+        assert_eq!(&vec![0, 5, 7], grouped.group_indices(2));
+        assert_eq!("37.5%", grouped.get((&String::from(PERCENT_COLUMN), 2)).to_string());
+        assert_eq!(None, grouped.rolled_up_rows(0));
+        assert_eq!(Some(&vec![0, 5, 7]), grouped.rolled_up_rows(2));
+
+        let top = TopGroups { count: 2, other: false };
+        assert_eq!(
+            2,
+            df.group_by(
+                &group_columns,
+                &[],
+                &GroupOptions {
+                    order: GroupOrder::SizeDesc,
+                    top: Some(top),
+                    ..GroupOptions::default()
+                }
+            )
+            .len()
+        );
+    }
+
+    #[test]
+    fn group_by_collapses_numbers_within_bucket() {
+        let column = Column {
+            name: String::from("latency"),
+            values: vec![
+                ColumnValue::Float(Float(1.0)),
+                ColumnValue::Float(Float(1.000_000_000_1)),
+                ColumnValue::Integer(2),
+                ColumnValue::Float(Float(1.02)),
+                ColumnValue::None,
+            ],
+        };
+        let records = (0..5).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let latency = String::from("latency");
+        let group_columns = vec![latency.clone()];
+        assert_eq!(5, df.group_by(&group_columns, &[], &GroupOptions::default()).len());
+
+        let buckets = [(latency.clone(), Buckets::Width(0.1))];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                buckets: &buckets,
+                ..GroupOptions::default()
+            },
+        );
+        assert_eq!(vec![3, 1, 1], (0..grouped.len()).map(|i| grouped.group_size(i)).collect::<Vec<_>>());
+        let mean = grouped.get((&latency, 0)).as_f64().unwrap_or_default();
+        assert!((mean - 1.0067).abs() < 1e-3, "unexpected mean {mean}");
+        assert_eq!(&ColumnValue::Float(Float(2.0)), grouped.get((&latency, 1)));
+        assert_eq!(&ColumnValue::None, grouped.get((&latency, 2)));
+        assert_eq!(Some(&vec![0, 1, 3]), grouped.rolled_up_rows(0));
+    }
+
+    #[test]
+    fn group_by_buckets_small_negative_numbers_with_zero() {
+        let column = Column {
+            name: String::from("delta"),
+            values: vec![
+                ColumnValue::Float(Float(0.01)),
+                ColumnValue::Float(Float(-0.01)),
+                ColumnValue::Integer(0),
+            ],
+        };
+        let records = (0..3).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let delta = String::from("delta");
+        let buckets = [(delta.clone(), Buckets::Width(1.0))];
+        let group_columns = [delta];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                buckets: &buckets,
+                ..GroupOptions::default()
+            },
+        );
+        assert_eq!(1, grouped.len());
+        assert_eq!(3, grouped.group_size(0));
+    }
+
+    #[test]
+    fn group_by_puts_numbers_into_ranges_including_lower_edge() {
+        let column = Column {
+            name: String::from("latency"),
+            values: [Some(500.0), Some(99.9), None, Some(100.0), Some(-3.0), Some(499.0), Some(1000.0)]
+                .into_iter()
+                .map(|v| v.map_or(ColumnValue::None, |v| ColumnValue::Float(Float(v))))
+                .collect(),
+        };
+        let records = (0..7).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let latency = String::from("latency");
+        let group_columns = vec![latency.clone()];
+        let buckets = [(latency.clone(), Buckets::Edges(vec![100.0, 500.0]))];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                buckets: &buckets,
+                ..GroupOptions::default()
+            },
+        );
+
+        let labels: Vec<String> = (0..grouped.len()).map(|i| grouped.get((&latency, i)).to_string()).collect();
+        assert_eq!(vec!["<100", "100-500", "500+", ""], labels);
+        let rows: Vec<&Vec<usize>> = (0..grouped.len()).map(|i| grouped.group_indices(i)).collect();
+        assert_eq!(vec![&vec![1, 4], &vec![3, 5], &vec![0, 6], &vec![2]], rows);
+    }
+
+    #[test]
+    fn group_by_truncates_datetimes_to_interval() {
+        let column = Column {
+            name: String::from("time"),
+            values: [(10, 59, 59), (9, 15, 0), (10, 0, 0), (9, 59, 30)]
+                .into_iter()
+                .map(|(h, m, s)| ColumnValue::DateTime(Utc.ymd(2022, 1, 5).and_hms(h, m, s)))
+                .collect(),
+        };
+        let records = (0..4).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let time = String::from("time");
+        let group_columns = vec![time.clone()];
+        let buckets = [(time.clone(), Buckets::Interval("1h".parse().expect("interval is valid")))];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                buckets: &buckets,
+                ..GroupOptions::default()
+            },
+        );
+
+        assert_eq!(2, grouped.len());
+        assert_eq!(&ColumnValue::DateTime(Utc.ymd(2022, 1, 5).and_hms(9, 0, 0)), grouped.get((&time, 0)));
+        assert_eq!(&vec![1, 3], grouped.group_indices(0));
+        assert_eq!(&ColumnValue::DateTime(Utc.ymd(2022, 1, 5).and_hms(10, 0, 0)), grouped.get((&time, 1)));
+        assert_eq!(&vec![0, 2], grouped.group_indices(1));
+    }
+
+    #[test]
+    fn interval_parses_minutes_hours_and_days() {
+        assert_eq!(Ok(Interval(15 * 60)), "15m".parse());
+        assert_eq!(Ok(Interval(3600)), "1h".parse());
+        assert_eq!(Ok(Interval(2 * 86400)), "2d".parse());
+        assert!("1w".parse::<Interval>().is_err());
+        assert!("0h".parse::<Interval>().is_err());
+        assert!("h".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn group_by_counts_distinct_values_of_columns() {
+        let string_column = |name: &str, values: &[Option<&str>]| Column {
+            name: String::from(name),
+            values: values
+                .iter()
+                .map(|v| v.map_or(ColumnValue::None, |v| ColumnValue::String(String::from(v))))
+                .collect(),
+        };
+        let status = string_column("status", &[Some("200"), Some("500"), Some("200"), Some("200"), Some("500")]);
+        let user = string_column("user", &[Some("ann"), Some("bob"), Some("ann"), Some("cid"), None]);
+        let records = (0..5).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([status, user].into_iter().map(|c| (c.name.clone(), c)).collect(), records);
+        let group_columns = [String::from("status")];
+        let grouped = df.group_by(
+            &group_columns,
+            &[],
+            &GroupOptions {
+                distinct_columns: &[String::from("user")],
+                ..GroupOptions::default()
+            },
+        );
+
+        let distinct = distinct_column_name("user");
+        assert_eq!(vec!["status", "distinct user", "%"], grouped.column_names());
+        assert_eq!(&ColumnValue::Integer(2), grouped.get((&distinct, 0)));
+        assert_eq!(&ColumnValue::Integer(1), grouped.get((&distinct, 1)));
+    }
+
+    #[test]
+    fn group_by_represents_extra_columns() {
+        let integer_column = |name: &str, values: &[i64]| Column {
+            name: String::from(name),
+            values: values.iter().copied().map(ColumnValue::Integer).collect(),
+        };
+        let status = integer_column("status", &[200, 500, 200, 200]);
+        let latency = integer_column("latency", &[30, 10, 90, 60]);
+        let records = (0..4).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([status, latency].into_iter().map(|c| (c.name.clone(), c)).collect(), records);
+        let group_columns = [String::from("status")];
+        let extra_columns = [String::from("latency")];
+        let latency_of = |representative| {
+            let representatives = [(String::from("latency"), representative)];
+            let options = GroupOptions {
+                representatives: &representatives,
+                ..GroupOptions::default()
+            };
+            let grouped = df.group_by(&group_columns, &extra_columns, &options);
+            (0..grouped.len())
+                .map(|i| grouped.get((&extra_columns[0], i)).clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![ColumnValue::Integer(30), ColumnValue::Integer(10)], latency_of(Representative::First));
+        assert_eq!(vec![ColumnValue::Integer(90), ColumnValue::Integer(10)], latency_of(Representative::Max));
+        assert_eq!(vec![ColumnValue::Integer(3), ColumnValue::Integer(1)], latency_of(Representative::DistinctCount));
+    }
+
+    #[test]
+    fn group_column_warnings_report_constant_and_unique_columns() {
+        let integer_column = |name: &str, values: &[i64]| Column {
+            name: String::from(name),
+            values: values.iter().copied().map(ColumnValue::Integer).collect(),
+        };
+        let columns = [
+            integer_column("host", &[1, 1, 1]),
+            integer_column("id", &[1, 2, 3]),
+            integer_column("status", &[200, 500, 200]),
+        ];
+        let records = (0..3).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), records);
+        let names: Vec<String> = ["host", "id", "status"].iter().map(|s| String::from(*s)).collect();
+        assert_eq!(
+            vec![
+                "group column host has the same value in every row",
+                "group column id has a distinct value in every row"
+            ],
+            df.group_column_warnings(&names, &GroupOptions::default())
+        );
+
+        // values of the same bucket go to one group
+        let buckets = [(String::from("id"), Buckets::Width(10.0))];
+        let options = GroupOptions {
+            buckets: &buckets,
+            ..GroupOptions::default()
+        };
+        assert_eq!(
+            vec![
+                "group column host has the same value in every row",
+                "group column id has the same value in every row"
+            ],
+            df.group_column_warnings(&names, &options)
+        );
+    }
+
+    #[test]
+    fn sort_by_column_orders_groups_by_their_value() {
+        let values = ["web", "db", "cache", "db", "web", "web"];
+        let column = Column {
+            name: String::from("host"),
+            values: values.iter().map(|v| ColumnValue::String(String::from(*v))).collect(),
+        };
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let group_columns = [String::from("host")];
+        let percent = String::from(PERCENT_COLUMN);
+        let hosts = |order| {
+            let mut grouped = df.group_by(&group_columns, &[], &GroupOptions::default());
+            grouped.sort_by_column(&ColumnOrder {
+                column: String::from("host"),
+                order,
+            });
+            // shares of the groups move with their groups
+            (0..grouped.len())
+                .map(|i| (grouped.get((&group_columns[0], i)).to_string(), grouped.group_size(i), grouped.get((&percent, i)).as_f64()))
+                .collect::<Vec<_>>()
+        };
+        let (cache, db, web) = (
+            (String::from("cache"), 1, Some(100.0 / 6.0)),
+            (String::from("db"), 2, Some(200.0 / 6.0)),
+            (String::from("web"), 3, Some(50.0)),
+        );
+        assert_eq!(vec![cache.clone(), db.clone(), web.clone()], hosts(SortOrder::Asc));
+        assert_eq!(vec![web, db, cache], hosts(SortOrder::Desc));
+    }
+
+    #[test]
+    fn filter_on_group_columns_matches_scan() {
+        let columns: indexmap::IndexMap<String, Column> = [("status", [500, 200, 500, 404, 500]), ("port", [80, 80, 443, 80, 80])]
+            .into_iter()
+            .map(|(name, values)| {
+                let column = Column {
+                    name: String::from(name),
+                    values: values.into_iter().map(ColumnValue::Integer).collect(),
+                };
+                (column.name.clone(), column)
+            })
+            .collect();
+        let records = (0..5).map(|_| serde_json::json!({})).collect();
+        let mut df = MaterializedDataFrame::new(columns, records);
+        let status = String::from("status");
+        let (e500, e302, e80) = (ColumnValue::Integer(500), ColumnValue::Integer(302), ColumnValue::Integer(80));
+        let filters: Vec<HashMap<&str, &ColumnValue>> = vec![
+            [("status", &e500)].into_iter().collect(),
+            [("status", &e500), ("port", &e80)].into_iter().collect(),
+            [("status", &e302)].into_iter().collect(),
+            [("port", &e80)].into_iter().collect(),
+        ];
+        let scanned: Vec<Vec<usize>> = filters.iter().map(|f| df.filter(f).idx).collect();
+
+        df.group_by(&[status], &[], &GroupOptions::default());
+        let indexed: Vec<Vec<usize>> = filters.iter().map(|f| df.filter(f).idx).collect();
+        assert_eq!(vec![vec![0, 2, 4], vec![0, 4], vec![], vec![0, 1, 3, 4]], indexed);
+        assert_eq!(scanned, indexed);
+
+        // regrouping builds the index for the new columns
+        df.reset_group_index();
+        let port = [String::from("port")];
+        df.group_by(&port, &[], &GroupOptions::default());
+        assert_eq!(scanned, filters.iter().map(|f| df.filter(f).idx).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn filter_in_and_exclude_select_rows_by_listed_values() {
+        let values: Vec<ColumnValue> = [500, 200, 502, 503, 500].into_iter().map(ColumnValue::Integer).collect();
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let column = Column {
+            name: String::from("status"),
+            values,
+        };
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let status = String::from("status");
+        let filter = [(status.clone(), vec![ColumnValue::Integer(500), ColumnValue::Integer(502)])]
+            .into_iter()
+            .collect();
+        let filtered = df.filter_in(&filter);
+
+        let actual: Vec<&ColumnValue> = (0..filtered.len()).map(|i| filtered.get((&status, i))).collect();
+        assert_eq!(vec![&ColumnValue::Integer(500), &ColumnValue::Integer(502), &ColumnValue::Integer(500)], actual);
+        assert_eq!(5, df.filter(&HashMap::new()).len());
+
+        let filter = [("status", &ColumnValue::Integer(500))].into_iter().collect();
+        let excluded = df.exclude(&filter);
+        let actual: Vec<&ColumnValue> = (0..excluded.len()).map(|i| excluded.get((&status, i))).collect();
+        assert_eq!(vec![&ColumnValue::Integer(200), &ColumnValue::Integer(502), &ColumnValue::Integer(503)], actual);
+    }
+
+    #[test]
+    fn remove_listed_drops_rows_from_every_group() {
+        let values: Vec<ColumnValue> = ["alice", "system", "bob", "system"]
+            .iter()
+            .map(|v| ColumnValue::String(String::from(*v)))
+            .collect();
+        let records = values.iter().map(|v| serde_json::json!({ "user": v.to_string() })).collect();
+        let column = Column {
+            name: String::from("user"),
+            values,
+        };
+        let mut df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let excluded = [(String::from("user"), vec![ColumnValue::String(String::from("system"))])]
+            .into_iter()
+            .collect();
+        df.remove_listed(&excluded);
+
+        assert_eq!(2, df.len());
+        assert!(df.raw(1).contains("bob"), "unexpected record {}", df.raw(1));
+        let group_columns = [String::from("user")];
+        let grouped = df.group_by(&group_columns, &[], &GroupOptions::default());
+        let users: Vec<String> = (0..grouped.len())
+            .map(|i| grouped.get((&group_columns[0], i)).to_string())
+            .collect();
+        assert_eq!(vec!["alice", "bob"], users);
+        assert_eq!("50.0%", grouped.get((&String::from(PERCENT_COLUMN), 0)).to_string());
+    }
 }
@@ -0,0 +1,403 @@
+use crate::io::dataframe::{Column, ColumnValue, DataFrame, Float, MaterializedDataFrame};
+use crate::io::input::InputAttributeType;
+use serde::Deserialize;
+use std::error::Error;
+use string_error::into_err;
+
+/// Expression computing a derived column from other columns of the same row.
+///
+/// Operands are column names (dotted names are allowed), numbers and quoted strings, combined with
+/// `+ - * /`, unary minus and parentheses. Numbers support every operator, strings can only be concatenated with `+`.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Expression {
+    Column(String),
+    Literal(ColumnValue),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn verb(self) -> &'static str {
+        match self {
+            Operator::Add => "add",
+            Operator::Subtract => "subtract",
+            Operator::Multiply => "multiply",
+            Operator::Divide => "divide",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Number(ColumnValue),
+    Text(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+impl Expression {
+    pub fn parse(input: &str) -> Result<Expression, String> {
+        let tokens = tokenize(input)?;
+        let mut position = 0;
+        let expression = parse_sum(&tokens, &mut position)?;
+        if position != tokens.len() {
+            return Err(format!("unexpected {:?} in expression {input}", tokens[position]));
+        }
+        Ok(expression)
+    }
+
+    /// Returns names of the columns the expression reads.
+    pub fn columns(&self) -> Vec<&String> {
+        match self {
+            Expression::Column(name) => vec![name],
+            Expression::Literal(_) => Vec::new(),
+            Expression::Negate(operand) => operand.columns(),
+            Expression::Binary(left, _, right) => {
+                let mut result = left.columns();
+                result.extend(right.columns());
+                result
+            }
+        }
+    }
+
+    /// Returns the type of the values of the expression given the types of the columns it reads, `Auto` if it depends on the values.
+    ///
+    /// Fails if an operator can't be applied to the types, operands of `Auto` columns are only checked per record.
+    pub fn value_type(&self, column_type: &impl Fn(&str) -> InputAttributeType) -> Result<InputAttributeType, String> {
+        use InputAttributeType::{Auto, Float, Integer};
+        match self {
+            Expression::Column(name) => Ok(column_type(name)),
+            Expression::Literal(value) => Ok(literal_type(value)),
+            Expression::Negate(operand) => match operand.value_type(column_type)? {
+                t @ (Auto | Integer | Float) => Ok(t),
+                t => Err(format!("cannot negate {}", attr_type_name(t))),
+            },
+            Expression::Binary(left, operator, right) => match (left.value_type(column_type)?, right.value_type(column_type)?) {
+                (Auto, _) | (_, Auto) => Ok(Auto),
+                (InputAttributeType::String, InputAttributeType::String) if *operator == Operator::Add => Ok(InputAttributeType::String),
+                (Integer, Integer) if *operator != Operator::Divide => Ok(Integer),
+                (Integer | Float, Integer | Float) => Ok(Float),
+                (a, b) => Err(format!("cannot {} {} and {}", operator.verb(), attr_type_name(a), attr_type_name(b))),
+            },
+        }
+    }
+
+    /// Evaluates the expression for every row, missing operands give missing values.
+    pub fn evaluate(&self, name: &str, df: &MaterializedDataFrame) -> Result<Column, Box<dyn Error>> {
+        let values = (0..df.len())
+            .map(|i| {
+                self.evaluate_row(df, i)
+                    .map_err(|e| into_err(format!("derived column {name}: record {}: {e}", i + 1)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Column {
+            name: String::from(name),
+            values,
+        })
+    }
+
+    fn evaluate_row(&self, df: &MaterializedDataFrame, index: usize) -> Result<ColumnValue, String> {
+        match self {
+            Expression::Column(name) => df
+                .get_column(name)
+                .map(|c| c[index].clone())
+                .ok_or_else(|| format!("unknown column {name}")),
+            Expression::Literal(value) => Ok(value.clone()),
+            Expression::Negate(operand) => negate(&operand.evaluate_row(df, index)?),
+            Expression::Binary(left, operator, right) => apply(*operator, &left.evaluate_row(df, index)?, &right.evaluate_row(df, index)?),
+        }
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        Expression::parse(&input)
+    }
+}
+
+fn negate(value: &ColumnValue) -> Result<ColumnValue, String> {
+    match value {
+        ColumnValue::None => Ok(ColumnValue::None),
+        ColumnValue::Integer(n) => n
+            .checked_neg()
+            .map(ColumnValue::Integer)
+            .ok_or_else(|| format!("integer overflow in -{n}")),
+        value => match value.as_f64() {
+            Some(n) => Ok(ColumnValue::Float(Float(-n))),
+            None => Err(format!("cannot negate {}", type_name(value))),
+        },
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn apply(operator: Operator, left: &ColumnValue, right: &ColumnValue) -> Result<ColumnValue, String> {
+    match (left, right) {
+        (ColumnValue::None, _) | (_, ColumnValue::None) => Ok(ColumnValue::None),
+        (ColumnValue::String(a), ColumnValue::String(b)) if operator == Operator::Add => Ok(ColumnValue::String(format!("{a}{b}"))),
+        (ColumnValue::Integer(a), ColumnValue::Integer(b)) if operator != Operator::Divide => {
+            let result = match operator {
+                Operator::Add => a.checked_add(*b),
+                Operator::Subtract => a.checked_sub(*b),
+                _ => a.checked_mul(*b),
+            };
+            result
+                .map(ColumnValue::Integer)
+                .ok_or_else(|| format!("integer overflow in {a} and {b}"))
+        }
+        (a, b) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => Ok(ColumnValue::Float(Float(match operator {
+                Operator::Add => a + b,
+                Operator::Subtract => a - b,
+                Operator::Multiply => a * b,
+                Operator::Divide => a / b,
+            }))),
+            _ => Err(format!("cannot {} {} and {}", operator.verb(), type_name(left), type_name(right))),
+        },
+    }
+}
+
+fn type_name(value: &ColumnValue) -> &'static str {
+    match value {
+        ColumnValue::None => "null",
+        ColumnValue::Boolean(_) => "boolean",
+        ColumnValue::Integer(_) => "integer",
+        ColumnValue::Float(_) | ColumnValue::Percent(_) => "float",
+        ColumnValue::String(_) => "string",
+        ColumnValue::DateTime(_) => "datetime",
+    }
+}
+
+fn literal_type(value: &ColumnValue) -> InputAttributeType {
+    match value {
+        ColumnValue::None => InputAttributeType::Auto,
+        ColumnValue::Boolean(_) => InputAttributeType::Bool,
+        ColumnValue::Integer(_) => InputAttributeType::Integer,
+        ColumnValue::Float(_) | ColumnValue::Percent(_) => InputAttributeType::Float,
+        ColumnValue::String(_) => InputAttributeType::String,
+        ColumnValue::DateTime(_) => InputAttributeType::DateTime,
+    }
+}
+
+/// Names the type like `type_name` names its values.
+fn attr_type_name(attr_type: InputAttributeType) -> &'static str {
+    match attr_type {
+        InputAttributeType::Auto => "value",
+        InputAttributeType::Bool => "boolean",
+        InputAttributeType::Integer => "integer",
+        InputAttributeType::Float => "float",
+        InputAttributeType::String => "string",
+        InputAttributeType::DateTime => "datetime",
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '+' => tokens.push(Token::Operator(Operator::Add)),
+            '-' => tokens.push(Token::Operator(Operator::Subtract)),
+            '*' => tokens.push(Token::Operator(Operator::Multiply)),
+            '/' => tokens.push(Token::Operator(Operator::Divide)),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' | '\'' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(t) => text.push(t),
+                        None => return Err(format!("unterminated string in expression {input}")),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = String::from(c);
+                while let Some(d) = chars.next_if(|d| d.is_ascii_digit() || *d == '.') {
+                    number.push(d);
+                }
+                let value = if let Ok(n) = number.parse::<i64>() {
+                    ColumnValue::Integer(n)
+                } else {
+                    ColumnValue::Float(Float(
+                        number
+                            .parse()
+                            .map_err(|_| format!("invalid number {number} in expression {input}"))?,
+                    ))
+                };
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = String::from(c);
+                while let Some(n) = chars.next_if(|n| n.is_alphanumeric() || *n == '_' || *n == '.') {
+                    name.push(n);
+                }
+                tokens.push(Token::Name(name));
+            }
+            c => return Err(format!("unexpected character {c} in expression {input}")),
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_sum(tokens: &[Token], position: &mut usize) -> Result<Expression, String> {
+    let mut result = parse_product(tokens, position)?;
+    while let Some(Token::Operator(operator @ (Operator::Add | Operator::Subtract))) = tokens.get(*position) {
+        *position += 1;
+        result = Expression::Binary(Box::new(result), *operator, Box::new(parse_product(tokens, position)?));
+    }
+    Ok(result)
+}
+
+fn parse_product(tokens: &[Token], position: &mut usize) -> Result<Expression, String> {
+    let mut result = parse_operand(tokens, position)?;
+    while let Some(Token::Operator(operator @ (Operator::Multiply | Operator::Divide))) = tokens.get(*position) {
+        *position += 1;
+        result = Expression::Binary(Box::new(result), *operator, Box::new(parse_operand(tokens, position)?));
+    }
+    Ok(result)
+}
+
+fn parse_operand(tokens: &[Token], position: &mut usize) -> Result<Expression, String> {
+    let token = tokens.get(*position).ok_or("unexpected end of expression")?;
+    *position += 1;
+    match token {
+        Token::Name(name) => Ok(Expression::Column(name.clone())),
+        Token::Number(value) => Ok(Expression::Literal(value.clone())),
+        Token::Text(text) => Ok(Expression::Literal(ColumnValue::String(text.clone()))),
+        Token::Operator(Operator::Subtract) => match parse_operand(tokens, position)? {
+            // negative numbers are kept as literals
+            Expression::Literal(ColumnValue::Integer(n)) if n != i64::MIN => Ok(Expression::Literal(ColumnValue::Integer(-n))),
+            Expression::Literal(ColumnValue::Float(Float(n))) => Ok(Expression::Literal(ColumnValue::Float(Float(-n)))),
+            operand => Ok(Expression::Negate(Box::new(operand))),
+        },
+        Token::Open => {
+            let result = parse_sum(tokens, position)?;
+            if tokens.get(*position) != Some(&Token::Close) {
+                return Err(String::from("missing closing parenthesis in expression"));
+            }
+            *position += 1;
+            Ok(result)
+        }
+        t => Err(format!("unexpected {t:?} in expression")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, Float, MaterializedDataFrame};
+    use crate::io::expression::Expression;
+    use crate::io::input::InputAttributeType;
+
+    fn dataframe(columns: Vec<Column>) -> MaterializedDataFrame {
+        let records = columns[0].values.iter().map(|_| serde_json::json!({})).collect();
+        MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), records)
+    }
+
+    #[test]
+    fn evaluate_adds_numeric_columns() {
+        let df = dataframe(vec![
+            Column {
+                name: String::from("bytes_in"),
+                values: vec![ColumnValue::Integer(10), ColumnValue::Float(Float(1.5)), ColumnValue::None],
+            },
+            Column {
+                name: String::from("bytes_out"),
+                values: vec![ColumnValue::Integer(5), ColumnValue::Integer(2), ColumnValue::Integer(1)],
+            },
+        ]);
+        let expression = Expression::parse("bytes_in + bytes_out * 2").expect("expression is valid");
+        let actual = expression.evaluate("total", &df).expect("columns are numeric");
+        assert_eq!(vec![ColumnValue::Integer(20), ColumnValue::Float(Float(5.5)), ColumnValue::None], actual.values);
+    }
+
+    #[test]
+    fn evaluate_concatenates_strings() {
+        let df = dataframe(vec![
+            Column {
+                name: String::from("method"),
+                values: vec![ColumnValue::String(String::from("GET"))],
+            },
+            Column {
+                name: String::from("path"),
+                values: vec![ColumnValue::String(String::from("/users"))],
+            },
+        ]);
+        let expression = Expression::parse("method + ' ' + path").expect("expression is valid");
+        let actual = expression.evaluate("request", &df).expect("columns are strings");
+        assert_eq!(vec![ColumnValue::String(String::from("GET /users"))], actual.values);
+    }
+
+    #[test]
+    fn evaluate_rejects_mixed_types() {
+        let df = dataframe(vec![
+            Column {
+                name: String::from("s"),
+                values: vec![ColumnValue::String(String::from("a"))],
+            },
+            Column {
+                name: String::from("n"),
+                values: vec![ColumnValue::Integer(1)],
+            },
+        ]);
+        let expression = Expression::parse("s + n").expect("expression is valid");
+        let message = expression.evaluate("x", &df).err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("derived column x: record 1: cannot add string and integer"), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn evaluate_negates_operands() {
+        let df = dataframe(vec![Column {
+            name: String::from("n"),
+            values: vec![ColumnValue::Integer(3), ColumnValue::Float(Float(1.5)), ColumnValue::None],
+        }]);
+        assert_eq!(Ok(Expression::Literal(ColumnValue::Integer(-2))), Expression::parse("-2"));
+        let expression = Expression::parse("-n * -2 - -1").expect("expression is valid");
+        let actual = expression.evaluate("x", &df).expect("column is numeric");
+        assert_eq!(vec![ColumnValue::Integer(7), ColumnValue::Float(Float(4.0)), ColumnValue::None], actual.values);
+        assert!(Expression::parse("- -").is_err());
+    }
+
+    #[test]
+    fn value_type_follows_operators() {
+        let column_type = |name: &str| match name {
+            "n" => InputAttributeType::Integer,
+            "x" => InputAttributeType::Float,
+            "s" => InputAttributeType::String,
+            "t" => InputAttributeType::DateTime,
+            _ => InputAttributeType::Auto,
+        };
+        let value_type = |input: &str| Expression::parse(input).expect("expression is valid").value_type(&column_type);
+        assert_eq!(Ok(InputAttributeType::Integer), value_type("-n * 2 - n"));
+        assert_eq!(Ok(InputAttributeType::Float), value_type("n / 2"));
+        assert_eq!(Ok(InputAttributeType::Float), value_type("n + x"));
+        assert_eq!(Ok(InputAttributeType::String), value_type("s + 'x'"));
+        assert_eq!(Ok(InputAttributeType::Auto), value_type("s + a"));
+        assert_eq!(Err(String::from("cannot add integer and string")), value_type("n + s"));
+        assert_eq!(Err(String::from("cannot multiply string and string")), value_type("s * s"));
+        assert_eq!(Err(String::from("cannot negate datetime")), value_type("-t"));
+    }
+
+    #[test]
+    fn parse_reports_malformed_expressions() {
+        assert!(Expression::parse("a +").is_err());
+        assert!(Expression::parse("(a + b").is_err());
+        assert!(Expression::parse("a b").is_err());
+        assert_eq!(vec!["a", "b.c"], Expression::parse("(a - 1) / b.c").expect("expression is valid").columns());
+    }
+}
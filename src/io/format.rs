@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::io::dataframe::ColumnValue;
+
+/// Display format of the numbers of a column.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnFormat {
+    /// Sizes in bytes with binary units, e.g. `1.5 MiB`
+    Bytes,
+    /// Sizes in bytes with decimal units, e.g. `1.5 MB`
+    DecimalBytes,
+    /// Durations in milliseconds, e.g. `1m 23s` or `450ms`
+    Duration,
+    /// Durations in seconds, e.g. `2h 5m`
+    DurationSeconds,
+    /// Datetimes relative to now, e.g. `2m ago` or `in 5m`
+    Relative,
+}
+
+impl ColumnFormat {
+    /// Formats the value, returns `None` for values of types the format doesn't apply to.
+    pub fn format(self, value: &ColumnValue) -> Option<String> {
+        match (self, value) {
+            (ColumnFormat::Bytes, ColumnValue::Integer(n)) => {
+                Some(format_bytes(*n, 1024.0, &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]))
+            }
+            (ColumnFormat::DecimalBytes, ColumnValue::Integer(n)) => {
+                Some(format_bytes(*n, 1000.0, &["B", "kB", "MB", "GB", "TB", "PB", "EB"]))
+            }
+            (ColumnFormat::Duration, ColumnValue::Integer(n)) => Some(format_duration(n.unsigned_abs(), *n < 0)),
+            (ColumnFormat::DurationSeconds, ColumnValue::Integer(0)) => Some(String::from("0s")),
+            (ColumnFormat::DurationSeconds, ColumnValue::Integer(n)) => {
+                Some(format_duration(n.unsigned_abs().saturating_mul(1000), *n < 0))
+            }
+            (ColumnFormat::Relative, ColumnValue::DateTime(d)) => Some(format_relative(*d, Utc::now())),
+            _ => None,
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: i64, base: f64, units: &[&str]) -> String {
+    let mut size = bytes.unsigned_abs() as f64;
+    let mut unit = 0;
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+    let sign = if bytes < 0 { "-" } else { "" };
+    // small sizes keep a decimal place so that e.g. 1.5 MiB isn't shown as 2 MiB
+    match unit {
+        0 => format!("{bytes} B"),
+        _ if size < 10.0 => format!("{sign}{size:.1} {}", units[unit]),
+        _ => format!("{sign}{size:.0} {}", units[unit]),
+    }
+}
+
+/// Shows the largest unit of the time between the datetime and now, e.g. `3h ago`.
+fn format_relative(value: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let millis = (now - value).num_milliseconds();
+    if millis.unsigned_abs() < 1000 {
+        return String::from("now");
+    }
+    let text = format_duration(millis.unsigned_abs(), false);
+    let largest = text.split(' ').next().unwrap_or_default();
+    if millis > 0 {
+        format!("{largest} ago")
+    } else {
+        format!("in {largest}")
+    }
+}
+
+/// Shows the two largest units of the duration, e.g. `3d 4h`, durations under a second in milliseconds.
+fn format_duration(millis: u64, negative: bool) -> String {
+    const UNITS: [(&str, u64); 4] = [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1000)];
+
+    let sign = if negative { "-" } else { "" };
+    let Some(first) = UNITS.iter().position(|(_, unit)| millis >= *unit) else {
+        return format!("{sign}{millis}ms");
+    };
+    let (name, unit) = UNITS[first];
+    match UNITS.get(first + 1) {
+        Some((next_name, next_unit)) if millis % unit >= *next_unit => {
+            format!("{sign}{}{name} {}{next_name}", millis / unit, millis % unit / next_unit)
+        }
+        _ => format!("{sign}{}{name}", millis / unit),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::ColumnValue;
+    use crate::io::format::{format_duration, format_relative, ColumnFormat};
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn format_duration_shows_two_largest_units() {
+        assert_eq!("0ms", format_duration(0, false));
+        assert_eq!("450ms", format_duration(450, false));
+        assert_eq!("1s", format_duration(1000, false));
+        assert_eq!("1m 23s", format_duration(83_456, false));
+        assert_eq!("-2h 5m", format_duration(7_500_000, true));
+        assert_eq!("213503982334d 14h", format_duration(u64::MAX, false));
+        assert_eq!(Some(String::from("0s")), ColumnFormat::DurationSeconds.format(&ColumnValue::Integer(0)));
+        assert_eq!(Some(String::from("1h 1m")), ColumnFormat::DurationSeconds.format(&ColumnValue::Integer(3661)));
+    }
+
+    #[test]
+    fn format_relative_shows_past_and_future_times() {
+        let now = Utc.ymd(2021, 6, 1).and_hms(12, 0, 0);
+        assert_eq!("now", format_relative(now, now));
+        assert_eq!("2m ago", format_relative(now - Duration::seconds(150), now));
+        assert_eq!("3h ago", format_relative(now - Duration::minutes(200), now));
+        assert_eq!("in 5m", format_relative(now + Duration::minutes(5), now));
+        assert_eq!("2d ago", format_relative(now - Duration::hours(50), now));
+    }
+}
@@ -0,0 +1,186 @@
+use std::error::Error;
+use std::io::BufRead;
+use std::time::Duration;
+
+/// Returns true if the input names a URL rather than a file.
+pub fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Largest body read from a URL after decompressing it.
+pub const MAX_BODY_SIZE: u64 = 1 << 30;
+/// Redirects followed before the request fails.
+#[cfg(feature = "http")]
+const MAX_REDIRECTS: u32 = 8;
+#[cfg(feature = "http")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Opens the body of the URL fetched with a GET request, gzip bodies are decompressed while they are read.
+///
+/// Connecting, sending and every read fail after the timeout, responses other than 200 fail with their status
+/// once redirects are followed. Reading fails once the body is larger than `MAX_BODY_SIZE`.
+#[cfg(feature = "http")]
+pub fn open(url: &str, timeout: Duration) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    use string_error::into_err;
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .timeout_write(timeout)
+        .redirects(MAX_REDIRECTS)
+        .user_agent(concat!("group/", env!("CARGO_PKG_VERSION")))
+        .build();
+    match agent.get(url).call() {
+        Ok(response) if response.status() == 200 => Ok(decode(response.into_reader(), MAX_BODY_SIZE)?),
+        Ok(response) | Err(ureq::Error::Status(_, response)) => {
+            Err(into_err(format!("{url}: server responded with {} {}", response.status(), response.status_text())))
+        }
+        // transport errors name the URL already
+        Err(ureq::Error::Transport(e)) => Err(into_err(e.to_string())),
+    }
+}
+
+/// Fails since URLs are only read with the `http` feature.
+#[cfg(not(feature = "http"))]
+pub fn open(url: &str, _timeout: Duration) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    Err(string_error::into_err(format!("{url}: reading URLs needs the http feature, download the input first")))
+}
+
+/// Decompresses gzip bodies, the encoding of the response is already decoded but gzip files may be served as they are.
+#[cfg(feature = "http")]
+fn decode(body: impl std::io::Read + 'static, limit: u64) -> std::io::Result<Box<dyn BufRead>> {
+    let mut body = std::io::BufReader::new(body);
+    let reader: Box<dyn std::io::Read> = if body.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Box::new(flate2::bufread::MultiGzDecoder::new(body))
+    } else {
+        Box::new(body)
+    };
+    Ok(Box::new(std::io::BufReader::new(LimitedReader {
+        inner: reader,
+        limit,
+        read: 0,
+    })))
+}
+
+/// Reader failing once more than `limit` bytes are read, so that a small compressed body can't fill the memory.
+#[cfg(feature = "http")]
+struct LimitedReader<R> {
+    inner: R,
+    limit: u64,
+    read: u64,
+}
+
+#[cfg(feature = "http")]
+impl<R: std::io::Read> std::io::Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.read += len as u64;
+        if self.read > self.limit {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("body is larger than {} bytes", self.limit)));
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod test {
+    use crate::io::http::{decode, open};
+    use flate2::write::GzEncoder;
+    use std::fmt::Write as _;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    /// Serves the response to one request and returns the URL of the server.
+    fn serve(response: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("port is free");
+        let address = listener.local_addr().expect("listener has an address");
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("client connects");
+            // the request ends with an empty line
+            let mut reader = BufReader::new(&mut stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|len| len > 2) {
+                line.clear();
+            }
+            stream.write_all(&response).ok();
+        });
+        format!("http://{address}/logs.json")
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).expect("data is compressed");
+        encoder.finish().expect("data is compressed")
+    }
+
+    fn records(count: usize) -> String {
+        let mut result = String::new();
+        for i in 0..count {
+            writeln!(result, "{{\"status\": {}, \"path\": \"/logs/{i}\"}}", 200 + i % 3).expect("strings can be written");
+        }
+        result
+    }
+
+    fn read(url: &str) -> Result<String, String> {
+        let mut body = String::new();
+        open(url, Duration::from_secs(5))
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut body)
+            .map_err(|e| e.to_string())?;
+        Ok(body)
+    }
+
+    #[test]
+    fn open_decompresses_gzip_file() {
+        let expected = records(5000);
+        let body = gzip(expected.as_bytes());
+        assert!(body.len() > 2048);
+        let mut response =
+            format!("HTTP/1.1 200 OK\r\nContent-Type: application/gzip\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+        response.extend(body);
+        assert_eq!(Ok(expected), read(&serve(response)));
+    }
+
+    #[test]
+    fn open_decodes_gzip_encoded_chunked_body() {
+        let expected = records(200);
+        let body = gzip(expected.as_bytes());
+        let mut response = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        for chunk in body.chunks(1000) {
+            response.extend(format!("{:x}\r\n", chunk.len()).into_bytes());
+            response.extend(chunk);
+            response.extend(b"\r\n");
+        }
+        response.extend(b"0\r\n\r\n");
+        assert_eq!(Ok(expected), read(&serve(response)));
+    }
+
+    #[test]
+    fn open_follows_redirects() {
+        let target = serve(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n{\"a\": 1}".to_vec());
+        let url = serve(format!("HTTP/1.1 302 Found\r\nLocation: {target}\r\nContent-Length: 0\r\n\r\n").into_bytes());
+        assert_eq!(Ok(String::from("{\"a\": 1}")), read(&url));
+    }
+
+    #[test]
+    fn open_reports_status_of_failed_request() {
+        let url = serve(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec());
+        let message = read(&url).err().unwrap_or_default();
+        assert!(message.ends_with("logs.json: server responded with 404 Not Found"), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn open_fails_on_truncated_body() {
+        let url = serve(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n{\"a\": 1}".to_vec());
+        assert!(read(&url).is_err());
+    }
+
+    #[test]
+    fn decode_limits_decompressed_size() {
+        let body = gzip(&vec![b' '; 1 << 20]);
+        let mut reader = decode(std::io::Cursor::new(body), 1 << 16).expect("body starts like gzip");
+        let message = std::io::copy(&mut reader, &mut std::io::sink()).err().map(|e| e.to_string());
+        assert_eq!(Some(String::from("body is larger than 65536 bytes")), message);
+    }
+}
@@ -1,105 +1,727 @@
-use crate::io::dataframe::{Column, ColumnValue, MaterializedDataFrame};
-use crate::io::serialize::to_pretty_json;
-use chrono::{DateTime, Utc};
-use indexmap::IndexMap;
+use crate::io::csv;
+use crate::io::dataframe::{Column, ColumnValue, Float, MaterializedDataFrame, RawRecord, ScannedFile};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use indexmap::{IndexMap, IndexSet};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::error::Error;
+use std::fs::File;
+use std::io::BufRead;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
 use string_error::into_err;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Yaml,
+    Csv,
+}
+
+impl InputFormat {
+    /// Guesses the format from the file extension, falling back to JSON.
+    pub fn from_path(path: &std::path::Path) -> InputFormat {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("yml" | "yaml") => InputFormat::Yaml,
+            Some("csv") => InputFormat::Csv,
+            _ => InputFormat::Json,
+        }
+    }
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(InputFormat::Json),
+            "yaml" => Ok(InputFormat::Yaml),
+            "csv" => Ok(InputFormat::Csv),
+            _ => Err(format!("unknown input format {s}")),
+        }
+    }
+}
+
+/// Type an attribute is coerced to, values of `Auto` attributes keep the type of the input value.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputAttributeType {
+    #[default]
+    Auto,
+    String,
+    Integer,
+    Float,
+    DateTime,
+    #[serde(alias = "boolean")]
+    Bool,
+}
+
+/// Attribute to read, given in the spec either as a name or as `{name, type, format}`.
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(from = "RawAttributeSpec")]
+pub struct InputAttributeSpec {
+    pub name: String,
+    pub attr_type: InputAttributeType,
+    /// `strftime` format of datetime attributes, RFC 3339 is expected if not set
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawAttributeSpec {
+    Name(String),
+    Typed {
+        name: String,
+        #[serde(rename = "type", default)]
+        attr_type: InputAttributeType,
+        format: Option<String>,
+    },
+}
+
+impl From<RawAttributeSpec> for InputAttributeSpec {
+    fn from(raw: RawAttributeSpec) -> Self {
+        match raw {
+            RawAttributeSpec::Name(name) => InputAttributeSpec::from(name),
+            RawAttributeSpec::Typed { name, attr_type, format } => InputAttributeSpec { name, attr_type, format },
+        }
+    }
+}
+
+impl From<String> for InputAttributeSpec {
+    fn from(name: String) -> Self {
+        InputAttributeSpec {
+            name,
+            attr_type: InputAttributeType::Auto,
+            format: None,
+        }
+    }
+}
+
+impl From<&str> for InputAttributeSpec {
+    fn from(name: &str) -> Self {
+        InputAttributeSpec::from(String::from(name))
+    }
+}
+
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReadOptions<'a> {
+    /// Format of the input
+    pub format: InputFormat,
+    /// Parse input as a single JSON array instead of detecting the layout from the first character
+    pub single: bool,
+    /// Skip malformed lines of a stream instead of failing
+    pub skip_errors: bool,
+    /// Maximum number of records to read
+    pub limit: Option<usize>,
+    /// Keep RFC 3339 strings of untyped attributes as strings instead of parsing them as datetimes
+    pub no_datetime_inference: bool,
+    /// Accept trailing commas and concatenated arrays of JSON input, records of the arrays are read in order.
+    /// The whole input is read into memory.
+    pub lenient: bool,
+    /// Called with the number of bytes read so far every few kilobytes and at the end of the input
+    pub progress: Option<&'a dyn Fn(u64)>,
+    /// Called with the position and the error of every malformed line or value skipped with `skip_errors`
+    pub on_skip: Option<&'a OnSkip<'a>>,
+}
+
+/// Callback of `ReadOptions::on_skip`.
+pub type OnSkip<'a> = dyn Fn(SkipPosition, &serde_json::Error) + 'a;
+
+/// Position of a skipped malformed input.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SkipPosition {
+    /// Line of a stream of records, counted from 1
+    Line(usize),
+    /// Byte offset of a value of lenient input
+    Byte(usize),
+}
+
+impl std::fmt::Display for SkipPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipPosition::Line(line) => write!(f, "line {line}"),
+            SkipPosition::Byte(offset) => write!(f, "byte {offset}"),
+        }
+    }
+}
+
+impl ReadOptions<'_> {
+    fn report_skip(&self, position: SkipPosition, error: &serde_json::Error) {
+        if let Some(on_skip) = self.on_skip {
+            on_skip(position, error);
+        }
+    }
+}
+
+/// Number of bytes read between progress reports.
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+/// Reader reporting the number of bytes read to the progress callback.
+struct ProgressReader<'a, R> {
+    inner: R,
+    position: u64,
+    reported: u64,
+    finished: bool,
+    progress: Option<&'a dyn Fn(u64)>,
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+    fn new(inner: R, progress: Option<&'a dyn Fn(u64)>) -> Self {
+        ProgressReader {
+            inner,
+            position: 0,
+            reported: 0,
+            finished: false,
+            progress,
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.position += len as u64;
+        if let Some(progress) = self.progress {
+            if self.position - self.reported >= PROGRESS_INTERVAL {
+                self.reported = self.position;
+                progress(self.position);
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if let (Some(progress), false) = (self.progress, self.finished) {
+            self.finished = true;
+            progress(self.position);
+        }
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        if len == 0 && !buf.is_empty() {
+            self.finish();
+        }
+        self.advance(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressReader<'_, R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.inner.fill_buf()?.is_empty() {
+            self.finish();
+        }
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
+
+/// Reads the requested attributes from input and returns the dataframe with the number of skipped records.
 pub fn read_dataframe(
     reader: impl std::io::BufRead,
-    attributes: &[String],
-    as_single_object: bool,
-) -> Result<MaterializedDataFrame, Box<dyn Error>> {
-    let input: Vec<serde_json::Value> = if as_single_object {
-        serde_json::from_reader(reader)?
-    } else {
-        let mut result: Vec<serde_json::Value> = Vec::new();
-        let deserializer = serde_json::Deserializer::from_reader(reader);
-        for v in deserializer.into_iter::<serde_json::Value>() {
-            result.push(v?);
+    attributes: &[InputAttributeSpec],
+    options: &ReadOptions,
+) -> Result<(MaterializedDataFrame, usize), Box<dyn Error>> {
+    let (input, skipped) = read_records(reader, options)?;
+    Ok((build_dataframe(input, attributes, options)?, skipped))
+}
+
+/// Reads the requested attributes from the file like `read_dataframe`, keeping byte ranges of records instead of the records.
+///
+/// Records are read again from the file when shown, so only the attribute values stay in memory.
+/// Arrays, YAML, CSV and inputs other than regular files such as pipes can't be read back by range and are kept in memory.
+pub fn scan_dataframe(
+    path: &Path,
+    attributes: &[InputAttributeSpec],
+    options: &ReadOptions,
+) -> Result<(MaterializedDataFrame, usize), Box<dyn Error>> {
+    check_distinct_names(attributes)?;
+    let open = || File::open(path).map(std::io::BufReader::new);
+    let open_with_progress = || open().map(|reader| ProgressReader::new(reader, options.progress));
+    if options.format != InputFormat::Json
+        || options.single
+        || options.lenient
+        || !std::fs::metadata(path)?.is_file()
+        || starts_with_array(&mut open()?)?
+    {
+        return read_dataframe(open()?, attributes, options);
+    }
+
+    let attr_paths = attributes
+        .iter()
+        .map(attribute_path)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(into_err)?;
+    let mut columns: Vec<Vec<ColumnValue>> = attributes.iter().map(|_| Vec::new()).collect();
+    let mut records = Vec::new();
+    // taken before reading, records of a file changed afterwards are not read back
+    let file = Arc::new(ScannedFile::new(path)?);
+    let mut add_record = |record: &serde_json::Value, range: Range<u64>| -> Result<(), Box<dyn Error>> {
+        for ((attr, attr_path), values) in attributes.iter().zip(&attr_paths).zip(&mut columns) {
+            let value = extract_record_value(attr, attr_path, record, !options.no_datetime_inference)
+                .map_err(|e| into_err(format!("record {}: {e}", records.len() + 1)))?;
+            values.push(value);
         }
-        result
+        records.push(RawRecord::Offset { file: file.clone(), range });
+        Ok(())
     };
 
-    let mut columns: IndexMap<String, Column> = IndexMap::new();
-    for attr in attributes {
-        let result = extract_column(attr, &input);
-        match result {
-            Ok(column) => columns.insert(column.name.clone(), column),
-            Err(err) => return Err(err),
-        };
+    let limit = options.limit.unwrap_or(usize::MAX);
+    let mut count = 0;
+    let mut skipped = 0;
+    if options.skip_errors {
+        let mut reader = open_with_progress()?;
+        let mut line = String::new();
+        let mut offset = 0;
+        for number in 1.. {
+            line.clear();
+            let len = reader.read_line(&mut line)? as u64;
+            if len == 0 || count == limit {
+                break;
+            }
+            let range = offset..offset + len;
+            offset += len;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(v) => {
+                    add_record(&v, range)?;
+                    count += 1;
+                }
+                Err(e) => {
+                    options.report_skip(SkipPosition::Line(number), &e);
+                    skipped += 1;
+                }
+            }
+        }
+    } else {
+        let mut stream = serde_json::Deserializer::from_reader(open_with_progress()?).into_iter::<serde_json::Value>();
+        let mut offset = 0;
+        while count < limit {
+            match stream.next() {
+                Some(Ok(v)) => {
+                    let end = stream.byte_offset() as u64;
+                    add_record(&v, offset..end)?;
+                    offset = end;
+                    count += 1;
+                }
+                Some(Err(e)) => return Err(into_err(format!("record {}: {}", count + 1, e))),
+                None => break,
+            }
+        }
     }
-    let mut raw: Vec<String> = Vec::new();
-    for v in &input {
-        raw.push(to_pretty_json(v)?);
+
+    let columns = attributes
+        .iter()
+        .zip(columns)
+        .map(|(attr, values)| {
+            let column = Column {
+                name: attr.name.clone(),
+                values,
+            };
+            (column.name.clone(), column)
+        })
+        .collect();
+    Ok((MaterializedDataFrame::from_raw_records(columns, records), skipped))
+}
+
+/// Reads input records and returns them with the number of skipped records.
+pub fn read_records(reader: impl std::io::BufRead, options: &ReadOptions) -> Result<(Vec<serde_json::Value>, usize), Box<dyn Error>> {
+    let reader = ProgressReader::new(reader, options.progress);
+    match options.format {
+        InputFormat::Json => read_json_records(reader, options),
+        InputFormat::Yaml => Ok((read_yaml_records(reader, options)?, 0)),
+        InputFormat::Csv => Ok((csv::read_records(reader, options.limit)?, 0)),
     }
+}
 
-    Ok(MaterializedDataFrame::new(columns, raw))
+/// Extracts the requested attributes from records, RFC 3339 strings of untyped attributes become datetimes unless
+/// `options.no_datetime_inference` is set.
+pub fn build_dataframe(
+    input: Vec<serde_json::Value>,
+    attributes: &[InputAttributeSpec],
+    options: &ReadOptions,
+) -> Result<MaterializedDataFrame, Box<dyn Error>> {
+    check_distinct_names(attributes)?;
+    let infer_datetimes = !options.no_datetime_inference;
+    // columns are extracted independently, collecting preserves the order of attributes
+    let extracted: Vec<Result<Column, String>> = attributes
+        .par_iter()
+        .map(|attr| extract_column(attr, &input, infer_datetimes))
+        .collect();
+    let mut columns: IndexMap<String, Column> = IndexMap::new();
+    for result in extracted {
+        let column = result.map_err(into_err)?;
+        columns.insert(column.name.clone(), column);
+    }
+
+    Ok(MaterializedDataFrame::new(columns, input))
 }
 
-fn extract_column(name: &str, input: &[serde_json::Value]) -> Result<Column, Box<dyn Error>> {
-    let mut values: Vec<ColumnValue> = Vec::new();
-    let attr_path: Vec<&str> = name.split('.').collect();
-    if attr_path.is_empty() {
-        return Err(into_err(format!("invalid attribute name={}", name)));
+/// Fails if two attributes give columns of the same name, the later column would replace the earlier one.
+fn check_distinct_names(attributes: &[InputAttributeSpec]) -> Result<(), Box<dyn Error>> {
+    let mut names = HashSet::new();
+    match attributes.iter().find(|attr| !names.insert(&attr.name)) {
+        Some(attr) => Err(into_err(format!("several attributes give the column {}", attr.name))),
+        None => Ok(()),
     }
+}
 
-    for input_element in input {
-        let mut element = input_element;
-        let mut not_found = false;
-        for path_element in &attr_path {
-            if let serde_json::Value::Object(obj) = element {
-                if let Some(value) = obj.get(*path_element) {
-                    element = value;
-                } else {
-                    not_found = true;
+/// Number of leading records walked to discover flattened attributes.
+pub const FLATTEN_SAMPLE_SIZE: usize = 100;
+/// Objects nested deeper than this are not flattened.
+const FLATTEN_MAX_DEPTH: usize = 8;
+
+/// Returns dotted paths of scalar leaves of objects in the first records in the order they are found.
+/// Arrays and keys containing dots are skipped since such values can't be extracted.
+pub fn flatten_attributes(input: &[serde_json::Value]) -> Vec<String> {
+    fn walk(value: &serde_json::Value, path: &mut Vec<String>, result: &mut IndexSet<String>) {
+        match value {
+            serde_json::Value::Object(obj) => {
+                if path.len() == FLATTEN_MAX_DEPTH {
+                    return;
+                }
+                // keys are visited alphabetically, independent of the order of keys in the record
+                let mut entries: Vec<_> = obj.iter().filter(|(k, _)| !k.is_empty() && !k.contains('.')).collect();
+                entries.sort_by_key(|(k, _)| *k);
+                for (key, value) in entries {
+                    path.push(key.clone());
+                    walk(value, path, result);
+                    path.pop();
+                }
+            }
+            serde_json::Value::Array(_) => {}
+            _ => {
+                if !path.is_empty() {
+                    result.insert(path.join("."));
+                }
+            }
+        }
+    }
+
+    let mut result = IndexSet::new();
+    for record in input.iter().take(FLATTEN_SAMPLE_SIZE) {
+        walk(record, &mut Vec::new(), &mut result);
+    }
+    result.into_iter().collect()
+}
+
+fn read_json_records(mut reader: impl std::io::BufRead, options: &ReadOptions) -> Result<(Vec<serde_json::Value>, usize), Box<dyn Error>> {
+    let mut skipped = 0;
+    let limit = options.limit.unwrap_or(usize::MAX);
+    let input: Vec<serde_json::Value> = if options.lenient {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        return read_lenient_records(&contents, options);
+    } else if options.single || starts_with_array(&mut reader)? {
+        let mut result: Vec<serde_json::Value> = serde_json::from_reader(reader)?;
+        result.truncate(limit);
+        result
+    } else if options.skip_errors {
+        let mut result: Vec<serde_json::Value> = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            if result.len() == limit {
+                break;
+            }
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(v) => result.push(v),
+                Err(e) => {
+                    options.report_skip(SkipPosition::Line(i + 1), &e);
+                    skipped += 1;
+                }
+            }
+        }
+        result
+    } else {
+        let mut result: Vec<serde_json::Value> = Vec::new();
+        let deserializer = serde_json::Deserializer::from_reader(reader);
+        for v in deserializer.into_iter::<serde_json::Value>().take(limit) {
+            match v {
+                Ok(v) => result.push(v),
+                Err(e) => return Err(into_err(format!("record {}: {}", result.len() + 1, e))),
+            }
+        }
+        result
+    };
+
+    Ok((input, skipped))
+}
+
+/// Reads whitespace separated JSON values without their trailing commas, elements of arrays are read as records.
+///
+/// The whole input is read into memory to normalize it. Malformed values are skipped up to the end of the line the error
+/// is found on if `options.skip_errors` is set, errors point at the byte offset of the value in the input.
+fn read_lenient_records(contents: &str, options: &ReadOptions) -> Result<(Vec<serde_json::Value>, usize), Box<dyn Error>> {
+    let normalized = strip_trailing_commas(contents);
+    let limit = options.limit.unwrap_or(usize::MAX);
+    let mut result = Vec::new();
+    let mut skipped = 0;
+    let mut position = 0;
+    while position < normalized.len() && result.len() < limit {
+        let rest = &normalized[position..];
+        let mut values = serde_json::Deserializer::from_str(rest).into_iter::<serde_json::Value>();
+        loop {
+            let offset = values.byte_offset();
+            match values.next() {
+                None => {
+                    position = normalized.len();
                     break;
                 }
-            } else {
-                not_found = true;
+                Some(Ok(serde_json::Value::Array(records))) => result.extend(records),
+                Some(Ok(v)) => result.push(v),
+                Some(Err(e)) => {
+                    // the offset is past the separator of the previous value, whitespace before the value is skipped
+                    let start = position + offset + rest[offset..].len() - rest[offset..].trim_start().len();
+                    if !options.skip_errors {
+                        return Err(into_err(format!("value at byte {start}: {e}")));
+                    }
+                    options.report_skip(SkipPosition::Byte(start), &e);
+                    skipped += 1;
+                    // lines of the error are relative to the start of the remaining input
+                    position += rest.split_inclusive('\n').take(e.line().max(1)).map(str::len).sum::<usize>();
+                    break;
+                }
+            }
+            if result.len() >= limit {
                 break;
             }
         }
+    }
+    result.truncate(limit);
+    Ok((result, skipped))
+}
 
-        if not_found {
-            values.push(ColumnValue::None);
-        } else {
-            match extract_column_value(element) {
-                Ok(v) => values.push(v),
-                Err(e) => return Err(into_err(format!("failed to parse value={}: {}", element, e))),
+/// Replaces commas followed by the end of an object or an array by spaces, commas in strings are kept.
+///
+/// The length of the input is kept, so that byte offsets in the result are offsets in the input.
+fn strip_trailing_commas(input: &str) -> String {
+    let mut result = input.as_bytes().to_vec();
+    let mut in_string = false;
+    let mut escaped = false;
+    // a comma is replaced once the next token shows that it ends an object or an array
+    let mut comma = None;
+    for (i, c) in input.bytes().enumerate() {
+        if in_string {
+            in_string = escaped || c != b'"';
+            escaped = !escaped && c == b'\\';
+        } else if c == b',' {
+            comma = Some(i);
+        } else if !c.is_ascii_whitespace() {
+            if let (Some(comma), b'}' | b']') = (comma, c) {
+                result[comma] = b' ';
             }
+            comma = None;
+            in_string = c == b'"';
+        }
+    }
+    // only ASCII commas are replaced by ASCII spaces
+    String::from_utf8(result).expect("replacing commas keeps UTF-8")
+}
+
+/// Reads every YAML document as a record, a single document holding a sequence is read as a list of records.
+fn read_yaml_records(mut reader: impl std::io::BufRead, options: &ReadOptions) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    let mut result: Vec<serde_json::Value> = Vec::new();
+    for (i, document) in serde_yaml::Deserializer::from_str(&contents).enumerate() {
+        match serde_json::Value::deserialize(document) {
+            Ok(v) => result.push(v),
+            Err(e) => return Err(into_err(format!("record {}: {}", i + 1, e))),
+        }
+    }
+    if let [serde_json::Value::Array(_)] = result.as_slice() {
+        if let Some(serde_json::Value::Array(records)) = result.pop() {
+            result = records;
+        }
+    }
+    result.truncate(options.limit.unwrap_or(usize::MAX));
+
+    Ok(result)
+}
+
+/// Peeks at the first non-whitespace byte without consuming it.
+fn starts_with_array(reader: &mut impl std::io::BufRead) -> std::io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(false);
+        }
+        if let Some(c) = buf.iter().find(|c| !c.is_ascii_whitespace()) {
+            return Ok(*c == b'[');
         }
+        let len = buf.len();
+        reader.consume(len);
     }
+}
+
+/// Name of the attribute holding the whole record, used to read streams and arrays of scalars.
+pub const RECORD_ATTRIBUTE: &str = ".";
+
+/// Extracts the attribute from every record, dotted names walk nested objects and `.` is the record itself.
+fn extract_column(attr: &InputAttributeSpec, input: &[serde_json::Value], infer_datetimes: bool) -> Result<Column, String> {
+    let attr_path = attribute_path(attr)?;
+    let values = input
+        .iter()
+        .enumerate()
+        .map(|(i, record)| extract_record_value(attr, &attr_path, record, infer_datetimes).map_err(|e| format!("record {}: {e}", i + 1)))
+        .collect::<Result<_, _>>()?;
     Ok(Column {
-        name: String::from(name),
+        name: attr.name.clone(),
         values,
     })
 }
 
-fn extract_column_value(value: &serde_json::Value) -> Result<ColumnValue, Box<dyn Error>> {
+/// Splits the attribute name into keys of nested objects.
+fn attribute_path(attr: &InputAttributeSpec) -> Result<Vec<&str>, String> {
+    let name = attr.name.as_str();
+    let attr_path: Vec<&str> = if name == RECORD_ATTRIBUTE {
+        Vec::new()
+    } else {
+        name.split('.').collect()
+    };
+    if attr_path.iter().any(|p| p.is_empty()) {
+        return Err(format!("invalid attribute name={name}"));
+    }
+    Ok(attr_path)
+}
+
+fn extract_record_value(
+    attr: &InputAttributeSpec,
+    attr_path: &[&str],
+    record: &serde_json::Value,
+    infer_datetimes: bool,
+) -> Result<ColumnValue, String> {
+    let mut element = record;
+    for path_element in attr_path {
+        match element {
+            serde_json::Value::Object(obj) => match obj.get(*path_element) {
+                Some(value) => element = value,
+                None => return Ok(ColumnValue::None),
+            },
+            _ => return Ok(ColumnValue::None),
+        }
+    }
+    extract_typed_value(element, attr, infer_datetimes).map_err(|e| format!("failed to parse value={element}: {e}"))
+}
+
+fn extract_column_value(value: &serde_json::Value, infer_datetimes: bool) -> Result<ColumnValue, Box<dyn Error>> {
     match value {
         serde_json::Value::Bool(b) => Ok(ColumnValue::Boolean(*b)),
         serde_json::Value::Number(v) => {
             if let Some(n) = v.as_i64() {
                 Ok(ColumnValue::Integer(n))
+            } else if let Some(n) = v.as_f64() {
+                Ok(ColumnValue::Float(Float(n)))
             } else {
-                Err(into_err(format!("number={} is not i64", v)))
+                Err(into_err(format!("number={v} is not a valid float")))
             }
         }
         serde_json::Value::String(s) => match DateTime::parse_from_rfc3339(s) {
-            Ok(d) => Ok(ColumnValue::DateTime(d.with_timezone(&Utc {}))),
-            Err(_) => Ok(ColumnValue::String(s.clone())),
+            Ok(d) if infer_datetimes => Ok(ColumnValue::DateTime(d.with_timezone(&Utc {}))),
+            _ => Ok(ColumnValue::String(s.clone())),
         },
         serde_json::Value::Null => Ok(ColumnValue::None),
-        _ => Err(into_err(format!("unsupported value={}", value))),
+        _ => Err(into_err(format!("unsupported value={value}"))),
+    }
+}
+
+/// Converts the text to the type of the attribute the same way as a string value of a record.
+pub fn coerce_text(text: &str, attr: &InputAttributeSpec, infer_datetimes: bool) -> Result<ColumnValue, Box<dyn Error>> {
+    extract_typed_value(&serde_json::Value::String(String::from(text)), attr, infer_datetimes)
+}
+
+/// Converts the value to the type of the attribute the same way as a value of a record.
+pub fn coerce_value(value: &serde_json::Value, attr: &InputAttributeSpec, infer_datetimes: bool) -> Result<ColumnValue, Box<dyn Error>> {
+    extract_typed_value(value, attr, infer_datetimes)
+}
+
+/// Extracts the value as the type of the attribute, coercing strings and numbers where possible.
+fn extract_typed_value(value: &serde_json::Value, attr: &InputAttributeSpec, infer_datetimes: bool) -> Result<ColumnValue, Box<dyn Error>> {
+    if value.is_null() {
+        return Ok(ColumnValue::None);
+    }
+    match attr.attr_type {
+        InputAttributeType::Auto => extract_column_value(value, infer_datetimes),
+        InputAttributeType::String => match value {
+            serde_json::Value::String(s) => Ok(ColumnValue::String(s.clone())),
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Ok(ColumnValue::String(value.to_string())),
+            _ => Err(into_err(format!("value={value} is not a valid string"))),
+        },
+        InputAttributeType::Integer => match value {
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(ColumnValue::Integer)
+                .ok_or_else(|| into_err(format!("number={n} is not a valid integer"))),
+            serde_json::Value::String(s) => Ok(ColumnValue::Integer(s.trim().parse()?)),
+            _ => Err(into_err(format!("value={value} is not a valid integer"))),
+        },
+        InputAttributeType::Float => match value {
+            serde_json::Value::Number(n) => n
+                .as_f64()
+                .map(|f| ColumnValue::Float(Float(f)))
+                .ok_or_else(|| into_err(format!("number={n} is not a valid float"))),
+            serde_json::Value::String(s) => match s.trim().parse::<f64>()? {
+                // NaN can't be grouped or compared and infinities break sums, so neither is a value of a column
+                f if f.is_finite() => Ok(ColumnValue::Float(Float(f))),
+                _ => Err(into_err(format!("value={value} is not a finite float"))),
+            },
+            _ => Err(into_err(format!("value={value} is not a valid float"))),
+        },
+        InputAttributeType::DateTime => match value {
+            serde_json::Value::String(s) => parse_datetime(s, attr.format.as_deref()).map(ColumnValue::DateTime),
+            _ => Err(into_err(format!("value={value} is not a valid datetime"))),
+        },
+        InputAttributeType::Bool => extract_bool_value(value),
+    }
+}
+
+/// Parses the datetime with the format, a format without an offset is read as UTC.
+fn parse_datetime(s: &str, format: Option<&str>) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let Some(format) = format else {
+        return Ok(DateTime::parse_from_rfc3339(s)?.with_timezone(&Utc {}));
+    };
+    match DateTime::parse_from_str(s, format) {
+        Ok(d) => Ok(d.with_timezone(&Utc {})),
+        Err(_) => Ok(DateTime::<Utc>::from_utc(NaiveDateTime::parse_from_str(s, format)?, Utc)),
+    }
+}
+
+/// Accepts JSON booleans and their common string spellings.
+fn extract_bool_value(value: &serde_json::Value) -> Result<ColumnValue, Box<dyn Error>> {
+    match value {
+        serde_json::Value::Bool(b) => Ok(ColumnValue::Boolean(*b)),
+        serde_json::Value::String(s) => match s.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(ColumnValue::Boolean(true)),
+            "false" | "no" | "0" => Ok(ColumnValue::Boolean(false)),
+            _ => Err(into_err(format!("string={s} is not a valid boolean"))),
+        },
+        serde_json::Value::Null => Ok(ColumnValue::None),
+        _ => Err(into_err(format!("value={value} is not a valid boolean"))),
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::configuration::InputSpec;
-    use crate::io::dataframe::{Column, ColumnValue, MaterializedDataFrame};
-    use crate::io::input::read_dataframe;
+    use crate::io::dataframe::{Column, ColumnValue, DataFrame, Float, MaterializedDataFrame};
+    use crate::io::input::{
+        build_dataframe, flatten_attributes, read_dataframe, read_records, scan_dataframe, InputAttributeSpec, InputAttributeType,
+        InputFormat, ReadOptions, SkipPosition,
+    };
+    use crate::io::serialize::to_pretty_json;
     use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
     use indexmap::IndexMap;
 
@@ -121,7 +743,7 @@ mod test {
                     name: String::from($column_name),
                     values: vec![$($value),*]
                 }],
-                vec![$(String::from($serialized)),*],
+                vec![$(serde_json::from_str::<serde_json::Value>($serialized).expect("serialized value is valid JSON")),*],
             )
         };
     }
@@ -129,10 +751,24 @@ mod test {
     macro_rules! simple_spec {
         ($column_name:expr) => {
             InputSpec {
-                attrs: vec![String::from($column_name)],
+                attrs: vec![InputAttributeSpec::from($column_name)],
                 group_by: vec![String::from($column_name)],
                 show_in_grouped: vec![],
+                represent: std::collections::HashMap::new(),
+                distinct: vec![],
+                buckets: std::collections::HashMap::new(),
+                coalesce_empty: false,
                 timeline_column: None,
+                bar: None,
+                sort_groups: None,
+                split: vec![],
+                capture: vec![],
+                derived: vec![],
+                exclude: vec![],
+                display_order: vec![],
+                hidden: vec![],
+                formats: std::collections::HashMap::new(),
+                keys: crate::keys::KeyBindings::default(),
             }
         };
     }
@@ -163,7 +799,16 @@ mod test {
         let input = "{\"int\": 10}\n{\"int\": 20}\n";
         let spec = simple_spec!("int");
         let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, false);
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_parses_float_column() {
+        let input = "{\"f\": 1.5}\n{\"f\": 18446744073709551615}\n";
+        let spec = simple_spec!("f");
+        let expected = simple_dataframe!("f" => ColumnValue::Float(Float(1.5)), "{\"f\": 1.5}"; ColumnValue::Float(Float(18_446_744_073_709_551_615.0)), "{\"f\": 18446744073709551615}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -172,7 +817,7 @@ mod test {
         let input = "{\"d\": \"2022-01-05T00:50:03.432Z\"}\n{\"d\": \"2022-01-05T00:50:05Z\"}";
         let spec = simple_spec!("d");
         let expected = simple_dataframe!("d" => datetime_value!(2022, 1, 5, 0, 50, 3, 432), "{\n  \"d\": \"2022-01-05T00:50:03.432Z\"\n}"; datetime_value!(2022, 1, 5, 0, 50, 5, 0), "{\n  \"d\": \"2022-01-05T00:50:05Z\"\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, false);
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -182,7 +827,7 @@ mod test {
         let spec = simple_spec!("d");
         let expected =
             simple_dataframe!("d" => datetime_value!(2022, 1, 5, 0, 50, 3, 432), "{\n  \"d\": \"2022-01-05T03:50:03.432+03:00\"\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, false);
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -191,7 +836,7 @@ mod test {
         let input = "{\"s\": \"hello\"}\n{\"s\": \"world\"}\n";
         let spec = simple_spec!("s");
         let expected = simple_dataframe!("s" => string_value!("hello"), "{\n  \"s\": \"hello\"\n}"; string_value!("world"), "{\n  \"s\": \"world\"\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, false);
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -200,7 +845,7 @@ mod test {
         let input = "{\"s\": \"hello\"}\n{}\n";
         let spec = simple_spec!("s");
         let expected = simple_dataframe!("s" => string_value!("hello"), "{\n  \"s\": \"hello\"\n}"; ColumnValue::None, "{}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, false);
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -209,7 +854,11 @@ mod test {
         let input = "[{\"int\": 10}, {\"int\": 20}]";
         let spec = simple_spec!("int");
         let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, true);
+        let options = ReadOptions {
+            single: true,
+            ..ReadOptions::default()
+        };
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -218,7 +867,11 @@ mod test {
         let input = "[{\"s\": \"hello\"}, {\"s\": \"world\"}]";
         let spec = simple_spec!("s");
         let expected = simple_dataframe!("s" => string_value!("hello"), "{\n  \"s\": \"hello\"\n}"; string_value!("world"), "{\n  \"s\": \"world\"\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, true);
+        let options = ReadOptions {
+            single: true,
+            ..ReadOptions::default()
+        };
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -227,7 +880,11 @@ mod test {
         let input = "[{\"d\": \"2022-01-05T00:50:03.432Z\"}, {\"d\": \"2022-01-05T00:50:05Z\"}]";
         let spec = simple_spec!("d");
         let expected = simple_dataframe!("d" => datetime_value!(2022, 1, 5, 0, 50, 3, 432), "{\n  \"d\": \"2022-01-05T00:50:03.432Z\"\n}"; datetime_value!(2022, 1, 5, 0, 50, 5, 0), "{\n  \"d\": \"2022-01-05T00:50:05Z\"\n}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, true);
+        let options = ReadOptions {
+            single: true,
+            ..ReadOptions::default()
+        };
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
 
@@ -236,7 +893,434 @@ mod test {
         let input = "[{\"s\": \"hello\"}, {}]";
         let spec = simple_spec!("s");
         let expected = simple_dataframe!("s" => string_value!("hello"), "{\n  \"s\": \"hello\"\n}"; ColumnValue::None, "{}");
-        let actual = read_dataframe(input.as_bytes(), &spec.attrs, true);
+        let options = ReadOptions {
+            single: true,
+            ..ReadOptions::default()
+        };
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_skips_malformed_lines() {
+        let input = "{\"int\": 10}\n{\"int\": \n{\"int\": 20}\n";
+        let spec = simple_spec!("int");
+        let skips = std::cell::RefCell::new(Vec::new());
+        let on_skip = |position, _: &serde_json::Error| skips.borrow_mut().push(position);
+        let options = ReadOptions {
+            skip_errors: true,
+            on_skip: Some(&on_skip),
+            ..ReadOptions::default()
+        };
+        let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options);
+        assert_eq!(Some((expected, 1)), actual.ok());
+        assert_eq!(vec![SkipPosition::Line(2)], skips.into_inner());
+    }
+
+    #[test]
+    fn read_dataframe_reports_record_of_unsupported_value() {
+        let input = "{\"v\": 10}\n{\"v\": [1, 2]}\n";
+        let spec = simple_spec!("v");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default());
+        let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("record 2: failed to parse value=[1,2]"), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn read_dataframe_reports_record_of_malformed_json() {
+        let input = "{\"v\": 10}\n{\"v\": }\n";
+        let spec = simple_spec!("v");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default());
+        let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("record 2: "), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn read_dataframe_stops_at_limit() {
+        let input = "{\"int\": 10}\n{\"int\": 20}\n{\"int\": 30}\n";
+        let spec = simple_spec!("int");
+        let options = ReadOptions {
+            limit: Some(2),
+            ..ReadOptions::default()
+        };
+        let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_reports_progress_up_to_end_of_input() {
+        let input = "{\"int\": 10}\n".repeat(10_000);
+        let spec = simple_spec!("int");
+        let reported = std::cell::RefCell::new(Vec::new());
+        let progress = |read| reported.borrow_mut().push(read);
+        let options = ReadOptions {
+            progress: Some(&progress),
+            ..ReadOptions::default()
+        };
+        read_dataframe(input.as_bytes(), &spec.attrs, &options).expect("input is valid");
+        let reported = reported.into_inner();
+        assert!(reported.len() > 1, "unexpected reports: {reported:?}");
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]), "unexpected reports: {reported:?}");
+        assert_eq!(Some(&(input.len() as u64)), reported.last());
+    }
+
+    #[test]
+    fn read_dataframe_stops_at_limit_when_reading_single_object() {
+        let input = "[{\"int\": 10}, {\"int\": 20}, {\"int\": 30}]";
+        let spec = simple_spec!("int");
+        let options = ReadOptions {
+            single: true,
+            limit: Some(2),
+            ..ReadOptions::default()
+        };
+        let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_keeps_attribute_order_for_wide_input() {
+        let attrs: Vec<InputAttributeSpec> = (0..64).rev().map(|i| InputAttributeSpec::from(format!("c{i}"))).collect();
+        let record: Vec<String> = (0..64).map(|i| format!("\"c{i}\": {i}")).collect();
+        let input = format!("{{{}}}\n", record.join(", ")).repeat(3);
+        let (actual, _) = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default()).expect("input is valid");
+        assert_eq!(attrs.iter().map(|a| &a.name).collect::<Vec<_>>(), actual.column_names());
+        for (i, attr) in (0..64).rev().zip(&attrs) {
+            assert_eq!(vec![integer_value!(i); 3], actual[&attr.name].values);
+        }
+    }
+
+    #[test]
+    fn read_dataframe_renders_raw_value_with_sorted_keys() {
+        let input = "{\"b\": 1, \"a\": {\"d\": 2, \"c\": 3}}";
+        let spec = simple_spec!("b");
+        let (actual, _) = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).expect("input is valid");
+        assert_eq!("{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}", actual.raw(0));
+    }
+
+    #[test]
+    fn read_dataframe_accepts_trailing_commas_when_lenient() {
+        let input = "{\"status\": 200, \"path\": \"/a,}\",}\n{\"status\": 500, \"tags\": [1, 2,],}";
+        let spec = simple_spec!("status");
+        let options = ReadOptions {
+            lenient: true,
+            ..ReadOptions::default()
+        };
+        assert!(read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).is_err());
+        let (actual, _) = read_dataframe(input.as_bytes(), &spec.attrs, &options).expect("trailing commas are stripped");
+        assert_eq!(vec![ColumnValue::Integer(200), ColumnValue::Integer(500)], actual[&String::from("status")].values);
+        assert!(actual.raw(0).contains("/a,}"));
+    }
+
+    #[test]
+    fn read_dataframe_flattens_concatenated_arrays_when_lenient() {
+        let input = "[{\"int\": 10}, {\"int\": 20}]\n[{\"int\": 30}]";
+        let spec = simple_spec!("int");
+        let options = ReadOptions {
+            lenient: true,
+            ..ReadOptions::default()
+        };
+        let (actual, _) = read_dataframe(input.as_bytes(), &spec.attrs, &options).expect("arrays are concatenated");
+        let expected: Vec<ColumnValue> = [10, 20, 30].into_iter().map(ColumnValue::Integer).collect();
+        assert_eq!(expected, actual[&String::from("int")].values);
+    }
+
+    #[test]
+    fn read_dataframe_skips_malformed_values_when_lenient() {
+        let input = "[{\"int\": 10},]\n{\"int\": }\n{\"int\": 30,}";
+        let spec = simple_spec!("int");
+        let options = ReadOptions {
+            lenient: true,
+            ..ReadOptions::default()
+        };
+        let error = read_dataframe(input.as_bytes(), &spec.attrs, &options).err().map(|e| e.to_string());
+        assert!(error.expect("value is malformed").contains("value at byte 15: "));
+
+        let skips = std::cell::RefCell::new(Vec::new());
+        let on_skip = |position, _: &serde_json::Error| skips.borrow_mut().push(position);
+        let options = ReadOptions {
+            skip_errors: true,
+            on_skip: Some(&on_skip),
+            ..options
+        };
+        let (actual, skipped) = read_dataframe(input.as_bytes(), &spec.attrs, &options).expect("malformed values are skipped");
+        assert_eq!(1, skipped);
+        assert_eq!(vec![SkipPosition::Byte(15)], skips.into_inner());
+        assert_eq!(vec![ColumnValue::Integer(10), ColumnValue::Integer(30)], actual[&String::from("int")].values);
+    }
+
+    #[test]
+    fn read_dataframe_detects_stream_and_array_layouts() {
+        let stream = "\n  {\"int\": 10}\n{\"int\": 20}\n";
+        let array = "\n  [{\"int\": 10}, {\"int\": 20}]";
+        let spec = simple_spec!("int");
+        for input in [stream, array] {
+            let expected = simple_dataframe!("int" => integer_value!(10), "{\n  \"int\": 10\n}"; integer_value!(20), "{\n  \"int\": 20\n}");
+            let actual = read_dataframe(input.as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
+            assert_eq!(Some(expected), actual.ok());
+        }
+    }
+
+    #[test]
+    fn read_dataframe_parses_yaml_documents() {
+        let input = "s: hello\nint: 10\n---\ns: world\n";
+        let spec = simple_spec!("s");
+        let options = ReadOptions {
+            format: InputFormat::Yaml,
+            ..ReadOptions::default()
+        };
+        let expected = simple_dataframe!("s" => string_value!("hello"), "{\"int\": 10, \"s\": \"hello\"}"; string_value!("world"), "{\"s\": \"world\"}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
         assert_eq!(Some(expected), actual.ok());
     }
+
+    #[test]
+    fn read_dataframe_parses_yaml_sequence() {
+        let input = "- s: hello\n- s: world\n";
+        let spec = simple_spec!("s");
+        let options = ReadOptions {
+            format: InputFormat::Yaml,
+            ..ReadOptions::default()
+        };
+        let expected = simple_dataframe!("s" => string_value!("hello"), "{\"s\": \"hello\"}"; string_value!("world"), "{\"s\": \"world\"}");
+        let actual = read_dataframe(input.as_bytes(), &spec.attrs, &options).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn build_dataframe_merges_records_in_order() {
+        let first = "{\"s\": \"a\"}\n{\"s\": \"b\"}\n";
+        let second = "[{\"s\": \"c\"}, {}]";
+        let spec = simple_spec!("s");
+        let mut records = Vec::new();
+        for input in [first, second] {
+            let (mut r, _) = read_records(input.as_bytes(), &ReadOptions::default()).expect("input is valid");
+            records.append(&mut r);
+        }
+        let expected = simple_dataframe!("s" => string_value!("a"), "{\"s\": \"a\"}"; string_value!("b"), "{\"s\": \"b\"}"; string_value!("c"), "{\"s\": \"c\"}"; ColumnValue::None, "{}");
+        let actual = build_dataframe(records, &spec.attrs, &ReadOptions::default());
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_coerces_bool_spellings() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("b"),
+            attr_type: InputAttributeType::Bool,
+            format: None,
+        }];
+        for (spelling, expected) in [
+            ("true", true),
+            ("\"true\"", true),
+            ("\"TRUE\"", true),
+            ("\"yes\"", true),
+            ("\"1\"", true),
+            ("false", false),
+            ("\"false\"", false),
+            ("\"no\"", false),
+            ("\"0\"", false),
+        ] {
+            let input = format!("{{\"b\": {spelling}}}");
+            let (actual, _) = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default()).expect("boolean is valid");
+            assert_eq!(vec![ColumnValue::Boolean(expected)], actual[&String::from("b")].values, "spelling {spelling}");
+        }
+    }
+
+    #[test]
+    fn read_dataframe_rejects_unknown_bool_spelling() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("b"),
+            attr_type: InputAttributeType::Bool,
+            format: None,
+        }];
+        let actual = read_dataframe("{\"b\": \"maybe\"}".as_bytes(), &attrs, &ReadOptions::default());
+        let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("record 1: failed to parse value=\"maybe\""), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn read_dataframe_keeps_numeric_looking_string_as_string() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("zip"),
+            attr_type: InputAttributeType::String,
+            format: None,
+        }];
+        let input = "{\"zip\": \"01234\"}\n{\"zip\": 98765}\n";
+        let (actual, _) = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default()).expect("input is valid");
+        assert_eq!(vec![string_value!("01234"), string_value!("98765")], actual[&String::from("zip")].values);
+    }
+
+    #[test]
+    fn read_dataframe_coerces_numeric_strings() {
+        let attrs = vec![
+            InputAttributeSpec {
+                name: String::from("i"),
+                attr_type: InputAttributeType::Integer,
+                format: None,
+            },
+            InputAttributeSpec {
+                name: String::from("f"),
+                attr_type: InputAttributeType::Float,
+                format: None,
+            },
+        ];
+        let input = "{\"i\": \"42\", \"f\": \"2.5\"}\n{\"i\": 7, \"f\": 3}\n";
+        let (actual, _) = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default()).expect("input is valid");
+        assert_eq!(vec![integer_value!(42), integer_value!(7)], actual[&String::from("i")].values);
+        assert_eq!(vec![ColumnValue::Float(Float(2.5)), ColumnValue::Float(Float(3.0))], actual[&String::from("f")].values);
+    }
+
+    #[test]
+    fn read_dataframe_rejects_non_finite_floats() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("f"),
+            attr_type: InputAttributeType::Float,
+            format: None,
+        }];
+        for text in ["NaN", "inf", "-Infinity"] {
+            let input = format!("{{\"f\": 1.5}}\n{{\"f\": \"{text}\"}}\n");
+            let actual = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default());
+            let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+            assert!(message.contains(&format!("record 2: failed to parse value=\"{text}\"")), "unexpected error: {message}");
+            assert!(message.contains("is not a finite float"), "unexpected error: {message}");
+        }
+    }
+
+    #[test]
+    fn read_dataframe_parses_datetime_with_format() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("d"),
+            attr_type: InputAttributeType::DateTime,
+            format: Some(String::from("%d/%m/%Y %H:%M:%S")),
+        }];
+        let input = "{\"d\": \"05/01/2022 00:50:03\"}";
+        let (actual, _) = read_dataframe(input.as_bytes(), &attrs, &ReadOptions::default()).expect("input is valid");
+        assert_eq!(vec![datetime_value!(2022, 1, 5, 0, 50, 3, 0)], actual[&String::from("d")].values);
+    }
+
+    #[test]
+    fn read_dataframe_rejects_value_not_matching_type() {
+        let attrs = vec![InputAttributeSpec {
+            name: String::from("i"),
+            attr_type: InputAttributeType::Integer,
+            format: None,
+        }];
+        let actual = read_dataframe("{\"i\": \"abc\"}".as_bytes(), &attrs, &ReadOptions::default());
+        let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("record 1: failed to parse value=\"abc\""), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn read_dataframe_keeps_datetime_strings_without_inference() {
+        let input = "{\"v\": \"2022-01-05T00:50:03Z\"}";
+        let options = ReadOptions {
+            no_datetime_inference: true,
+            ..ReadOptions::default()
+        };
+        let (actual, _) = read_dataframe(input.as_bytes(), &[InputAttributeSpec::from("v")], &options).expect("input is valid");
+        assert_eq!(vec![string_value!("2022-01-05T00:50:03Z")], actual[&String::from("v")].values);
+
+        let typed = InputAttributeSpec {
+            name: String::from("v"),
+            attr_type: InputAttributeType::String,
+            format: None,
+        };
+        let (actual, _) = read_dataframe(input.as_bytes(), &[typed], &ReadOptions::default()).expect("input is valid");
+        assert_eq!(vec![string_value!("2022-01-05T00:50:03Z")], actual[&String::from("v")].values);
+    }
+
+    #[test]
+    fn read_dataframe_reads_stream_of_scalars_as_record_attribute() {
+        let spec = simple_spec!(".");
+        let expected = simple_dataframe!("." => integer_value!(1), "1"; integer_value!(2), "2");
+        let actual = read_dataframe("1\n2\n".as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+
+        let expected = simple_dataframe!("." => string_value!("a"), "\"a\""; string_value!("b"), "\"b\"");
+        let actual = read_dataframe("\"a\"\n\"b\"\n".as_bytes(), &spec.attrs, &ReadOptions::default()).map(|(df, _)| df);
+        assert_eq!(Some(expected), actual.ok());
+    }
+
+    #[test]
+    fn read_dataframe_rejects_attributes_giving_same_column() {
+        let attrs = vec![
+            InputAttributeSpec::from("status"),
+            InputAttributeSpec {
+                name: String::from("status"),
+                attr_type: InputAttributeType::String,
+                format: None,
+            },
+        ];
+        let actual = read_dataframe("{\"status\": 200}".as_bytes(), &attrs, &ReadOptions::default());
+        let message = actual.err().map(|e| e.to_string()).unwrap_or_default();
+        assert!(message.contains("several attributes give the column status"), "unexpected error: {message}");
+    }
+
+    #[test]
+    fn read_dataframe_rejects_empty_path_element() {
+        let spec = simple_spec!("a..b");
+        assert!(read_dataframe("{}".as_bytes(), &spec.attrs, &ReadOptions::default()).is_err());
+    }
+
+    #[test]
+    fn flatten_attributes_lists_scalar_leaves_in_order() {
+        let records = vec![
+            serde_json::json!({"user": {"id": 1, "name": "a"}, "tags": ["x"], "level": "info"}),
+            serde_json::json!({"request": {"method": "GET", "headers": {}}, "user": {"id": 2, "email": null}}),
+        ];
+        assert_eq!(vec!["level", "user.id", "user.name", "request.method", "user.email"], flatten_attributes(&records));
+    }
+
+    #[test]
+    fn flatten_attributes_stops_at_max_depth() {
+        let mut record = serde_json::json!(1);
+        for _ in 0..20 {
+            record = serde_json::json!({ "a": record });
+        }
+        assert!(flatten_attributes(&[record]).is_empty());
+    }
+
+    #[test]
+    fn scan_dataframe_keeps_only_attribute_values_in_memory() {
+        let path = std::env::temp_dir().join(format!("group-scan-{}.json", std::process::id()));
+        let payload = "x".repeat(1024);
+        let lines: Vec<String> = (0..10_000)
+            .map(|i| format!("{{\"id\": {i}, \"payload\": \"{payload}\"}}\n"))
+            .collect();
+        std::fs::write(&path, lines.concat()).expect("temporary file is writable");
+
+        let options = ReadOptions::default();
+        let result = scan_dataframe(&path, &[InputAttributeSpec::from("id")], &options);
+        let skipping = ReadOptions {
+            skip_errors: true,
+            ..ReadOptions::default()
+        };
+        let skipping_result = scan_dataframe(&path, &[InputAttributeSpec::from("id")], &skipping);
+
+        for (df, _) in [result.expect("input is valid"), skipping_result.expect("input is valid")] {
+            assert_eq!(10_000, df.len());
+            assert_eq!(0, df.records_in_memory());
+            assert_eq!(ColumnValue::Integer(9_999), df[&String::from("id")][9_999]);
+            let expected = to_pretty_json(&serde_json::json!({"id": 1234, "payload": payload})).expect("value is serializable");
+            assert_eq!(&expected, df.raw(1234));
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn scan_dataframe_does_not_read_records_of_changed_file() {
+        let path = std::env::temp_dir().join(format!("group-rotated-{}.json", std::process::id()));
+        std::fs::write(&path, "{\"id\": 1}\n{\"id\": 2}\n").expect("temporary file is writable");
+        let (df, _) = scan_dataframe(&path, &[InputAttributeSpec::from("id")], &ReadOptions::default()).expect("input is valid");
+        std::fs::write(&path, "{\"id\": 3}\n{\"id\": 4}\n{\"id\": 5}\n").expect("temporary file is writable");
+        assert!(
+            df.raw(0)
+                .ends_with("the file changed since it was read, reload it to show the record"),
+            "{}",
+            df.raw(0)
+        );
+        std::fs::remove_file(&path).ok();
+    }
 }
@@ -0,0 +1,67 @@
+use crate::io::dataframe::{ColumnValue, DataFrame};
+
+/// Renders the dataframe as a GitHub-flavored Markdown table, numeric columns are aligned right.
+pub fn to_markdown(df: &dyn DataFrame) -> String {
+    let names = df.column_names();
+    let mut lines = vec![
+        format_row(names.iter().map(|name| escape(name))),
+        format_row(
+            names
+                .iter()
+                .map(|name| String::from(if df.column(name).is_numeric() { "---:" } else { "---" })),
+        ),
+    ];
+    lines.extend((0..df.len()).map(|i| format_row(names.iter().map(|name| format_value(df.get((name, i)))))));
+    let mut table = lines.join("\n");
+    table.push('\n');
+    table
+}
+
+fn format_row(cells: impl Iterator<Item = String>) -> String {
+    let cells: Vec<String> = cells.collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn format_value(value: &ColumnValue) -> String {
+    match value {
+        ColumnValue::DateTime(d) => d.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        v => escape(&v.to_string()),
+    }
+}
+
+/// Escapes pipes so that they don't end the cell, line breaks would end the row and become spaces.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, MaterializedDataFrame};
+    use crate::io::markdown::to_markdown;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn to_markdown_escapes_pipes_and_aligns_numbers() {
+        let columns = vec![
+            Column {
+                name: String::from("command"),
+                values: vec![ColumnValue::String(String::from("ls | wc")), ColumnValue::None],
+            },
+            Column {
+                name: String::from("count"),
+                values: vec![ColumnValue::Integer(3), ColumnValue::Integer(12)],
+            },
+            Column {
+                name: String::from("ts"),
+                values: vec![ColumnValue::DateTime(Utc.ymd(2022, 1, 5).and_hms(0, 50, 3)), ColumnValue::None],
+            },
+        ];
+        let records = (0..2).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), records);
+        let expected = "| command | count | ts |\n\
+                        | --- | ---: | --- |\n\
+                        | ls \\| wc | 3 | 2022-01-05T00:50:03Z |\n\
+                        |  | 12 |  |\n";
+        assert_eq!(expected, to_markdown(&df));
+    }
+}
@@ -1,3 +1,9 @@
+pub mod csv;
 pub mod dataframe;
+pub mod expression;
+pub mod format;
+pub mod http;
 pub mod input;
+pub mod markdown;
 pub mod serialize;
+pub mod transform;
@@ -1,15 +1,64 @@
-use serde;
 use serde::Serialize;
 use serde_json;
 
-fn sort_alphabetically<T: Serialize, S: serde::Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
-    let value = serde_json::to_value(value).map_err(serde::ser::Error::custom)?;
-    value.serialize(serializer)
+/// Order of the keys of objects in pretty-printed records.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum KeyOrder {
+    /// Keys are sorted alphabetically at every level
+    #[default]
+    Alphabetical,
+    /// Keys keep the order of the input
+    Original,
 }
 
-#[derive(Serialize)]
-struct SortAlphabetically<T: Serialize>(#[serde(serialize_with = "sort_alphabetically")] T);
+impl std::str::FromStr for KeyOrder {
+    type Err = String;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alphabetical" => Ok(KeyOrder::Alphabetical),
+            "original" => Ok(KeyOrder::Original),
+            _ => Err(format!("unknown key order {s}")),
+        }
+    }
+}
+
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(entries.into_iter().map(|(k, v)| (k, sort_keys(v))).collect())
+        }
+        serde_json::Value::Array(values) => serde_json::Value::Array(values.into_iter().map(sort_keys).collect()),
+        v => v,
+    }
+}
+
+/// Pretty-prints the value with keys sorted alphabetically.
 pub fn to_pretty_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
-    serde_json::to_string_pretty(&SortAlphabetically(value))
+    to_ordered_pretty_json(value, KeyOrder::Alphabetical)
+}
+
+pub fn to_ordered_pretty_json<T: Serialize>(value: &T, order: KeyOrder) -> serde_json::Result<String> {
+    let value = serde_json::to_value(value)?;
+    match order {
+        KeyOrder::Alphabetical => serde_json::to_string_pretty(&sort_keys(value)),
+        KeyOrder::Original => serde_json::to_string_pretty(&value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::serialize::{to_ordered_pretty_json, KeyOrder};
+
+    #[test]
+    fn to_ordered_pretty_json_sorts_or_keeps_keys() {
+        let value: serde_json::Value =
+            serde_json::from_str(r#"{"time": 1, "level": "info", "ctx": {"z": 1, "a": 2}}"#).expect("JSON is valid");
+        let alphabetical = to_ordered_pretty_json(&value, KeyOrder::Alphabetical).expect("value is serializable");
+        assert_eq!("{\n  \"ctx\": {\n    \"a\": 2,\n    \"z\": 1\n  },\n  \"level\": \"info\",\n  \"time\": 1\n}", alphabetical);
+        let original = to_ordered_pretty_json(&value, KeyOrder::Original).expect("value is serializable");
+        assert_eq!("{\n  \"time\": 1,\n  \"level\": \"info\",\n  \"ctx\": {\n    \"z\": 1,\n    \"a\": 2\n  }\n}", original);
+    }
 }
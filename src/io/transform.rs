@@ -0,0 +1,143 @@
+use crate::io::dataframe::{Column, ColumnValue, DataFrame, MaterializedDataFrame};
+use crate::io::input::{coerce_text, InputAttributeSpec};
+use regex::Regex;
+use std::error::Error;
+use string_error::into_err;
+
+/// Name of a split part that is not kept as a column.
+pub const DISCARDED_PART: &str = "_";
+
+/// Splits values of the column by the separator into a column for every name of `into`.
+///
+/// Parts named `_` and parts beyond the listed names are dropped, values with fewer parts get missing values.
+/// Values other than strings are split by their displayed text.
+pub fn split_column(df: &mut MaterializedDataFrame, column: &str, separator: &str, into: &[String]) -> Result<(), Box<dyn Error>> {
+    let parts: Vec<Vec<ColumnValue>> = df
+        .column(column)
+        .values
+        .iter()
+        .map(|value| {
+            let text = match value {
+                ColumnValue::None => return vec![ColumnValue::None; into.len()],
+                ColumnValue::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            let mut parts: Vec<ColumnValue> = text
+                .split(separator)
+                .take(into.len())
+                .map(|p| ColumnValue::String(String::from(p)))
+                .collect();
+            parts.resize(into.len(), ColumnValue::None);
+            parts
+        })
+        .collect();
+
+    for (i, name) in into.iter().enumerate().filter(|(_, name)| *name != DISCARDED_PART) {
+        let values = parts.iter().map(|row| row[i].clone()).collect();
+        df.add_column(Column {
+            name: name.clone(),
+            values,
+        })
+        .map_err(|e| into_err(format!("split column {name}: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Adds the column with the capture group of the regex in values of the column, coerced to the type of `into`.
+///
+/// Values that don't match get missing values, RFC 3339 captures of untyped attributes become datetimes if `infer_datetimes` is set.
+pub fn capture_column(
+    df: &mut MaterializedDataFrame,
+    column: &str,
+    regex: &Regex,
+    group: usize,
+    into: &InputAttributeSpec,
+    infer_datetimes: bool,
+) -> Result<(), Box<dyn Error>> {
+    let values = df
+        .column(column)
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let text = match value {
+                ColumnValue::None => return Ok(ColumnValue::None),
+                ColumnValue::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            match regex.captures(&text).and_then(|c| c.get(group)) {
+                Some(m) => coerce_text(m.as_str(), into, infer_datetimes)
+                    .map_err(|e| into_err(format!("capture column {}: record {}: {e}", into.name, i + 1))),
+                None => Ok(ColumnValue::None),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    df.add_column(Column {
+        name: into.name.clone(),
+        values,
+    })
+    .map_err(|e| into_err(format!("capture column {}: {e}", into.name)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, DataFrame, MaterializedDataFrame};
+    use crate::io::input::{InputAttributeSpec, InputAttributeType};
+    use crate::io::transform::{capture_column, split_column};
+    use regex::Regex;
+
+    fn string_value(s: &str) -> ColumnValue {
+        ColumnValue::String(String::from(s))
+    }
+
+    #[test]
+    fn split_column_creates_column_for_every_named_part() {
+        let column = Column {
+            name: String::from("path"),
+            values: vec![string_value("/api/v2/users"), string_value("/health"), ColumnValue::None],
+        };
+        let records = (0..3).map(|_| serde_json::json!({})).collect();
+        let mut df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let into: Vec<String> = ["_", "api", "version", "resource"].iter().map(|s| String::from(*s)).collect();
+        split_column(&mut df, "path", "/", &into).expect("part columns are new");
+
+        let names: Vec<&String> = df.column_names();
+        assert_eq!(vec!["path", "api", "version", "resource"], names);
+        assert_eq!(vec![string_value("api"), string_value("health"), ColumnValue::None], df.column("api").values);
+        assert_eq!(vec![string_value("v2"), ColumnValue::None, ColumnValue::None], df.column("version").values);
+        assert_eq!(vec![string_value("users"), ColumnValue::None, ColumnValue::None], df.column("resource").values);
+    }
+
+    #[test]
+    fn capture_column_extracts_typed_group() {
+        let column = Column {
+            name: String::from("name"),
+            values: vec![string_value("order-12345"), string_value("refund")],
+        };
+        let records = (0..2).map(|_| serde_json::json!({})).collect();
+        let mut df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let into = InputAttributeSpec {
+            name: String::from("order_id"),
+            attr_type: InputAttributeType::Integer,
+            format: None,
+        };
+        let regex = Regex::new(r"order-(\d+)").expect("regex is valid");
+        capture_column(&mut df, "name", &regex, 1, &into, true).expect("captures are integers");
+        assert_eq!(vec![ColumnValue::Integer(12345), ColumnValue::None], df.column("order_id").values);
+    }
+
+    #[test]
+    fn capture_column_infers_datetimes_only_if_requested() {
+        let column = Column {
+            name: String::from("s"),
+            values: vec![string_value("2024-01-01T00:00:00Z")],
+        };
+        let records = vec![serde_json::json!({})];
+        let mut df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let regex = Regex::new("(.*)").expect("regex is valid");
+        capture_column(&mut df, "s", &regex, 1, &InputAttributeSpec::from("id"), false).expect("capture column is new");
+        capture_column(&mut df, "s", &regex, 1, &InputAttributeSpec::from("time"), true).expect("capture column is new");
+        assert_eq!(vec![string_value("2024-01-01T00:00:00Z")], df.column("id").values);
+        assert!(matches!(df.column("time").values[0], ColumnValue::DateTime(_)));
+    }
+}
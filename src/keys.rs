@@ -0,0 +1,236 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Action of the table view that keys can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Jump,
+    Search,
+    ColumnLeft,
+    ColumnRight,
+    Expand,
+    Legend,
+    Focus,
+    Exclude,
+    Bookmark,
+    ShowBookmarks,
+    Timeline,
+    Regroup,
+    Level,
+    Back,
+    Quit,
+    Copy,
+    Pick,
+    Help,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveUp => "move selection up",
+            Action::MoveDown => "move selection down",
+            Action::PageUp => "move selection up by a page",
+            Action::PageDown => "move selection down by a page",
+            Action::First => "select the first row",
+            Action::Last => "select the last row",
+            Action::Jump => "type a row number and press Enter to select it",
+            Action::Search => "type text and press Enter to highlight it and select the next row with it",
+            Action::ColumnLeft => "select the column to the left, group columns stay in place",
+            Action::ColumnRight => "select the column to the right, group columns stay in place",
+            Action::Expand => "show the full value of the selected cell",
+            Action::Legend => "show the colors of the values of the selected column",
+            Action::Focus => "show rows of the selected group, toggle the record card",
+            Action::Exclude => "show rows of all groups except the selected one",
+            Action::Bookmark => "bookmark the selected row of a filtered view or remove its bookmark",
+            Action::ShowBookmarks => "show bookmarked rows, press again to go back",
+            Action::Timeline => "show or hide the timeline column of groups",
+            Action::Regroup => "choose the columns to group by, Space checks a column and Enter regroups",
+            Action::Level => "go back to the level with the number shown in the footer",
+            Action::Back => "hide the record card, go back to groups or quit",
+            Action::Quit => "quit",
+            Action::Copy => "copy the record shown in the card to the clipboard",
+            Action::Pick => "print the record shown in the card on exit",
+            Action::Help => "show this help",
+        }
+    }
+}
+
+/// Key that actions can be bound to, independent of the terminal library.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+/// Keys bound to every action, in the order they are listed in help.
+///
+/// Deserializing reads the keys of the configured actions and rejects them the same way as `new`.
+#[derive(PartialEq, Debug, Deserialize)]
+#[serde(try_from = "HashMap<Action, Vec<String>>")]
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: vec![
+                (Action::MoveUp, vec![Key::Char('w'), Key::Up]),
+                (Action::MoveDown, vec![Key::Char('s'), Key::Down]),
+                (Action::PageUp, vec![Key::PageUp]),
+                (Action::PageDown, vec![Key::PageDown]),
+                (Action::First, vec![Key::Home]),
+                (Action::Last, vec![Key::End]),
+                (Action::Jump, vec![Key::Char('g')]),
+                (Action::Search, vec![Key::Char('/')]),
+                (Action::ColumnLeft, vec![Key::Left]),
+                (Action::ColumnRight, vec![Key::Right]),
+                (Action::Expand, vec![Key::Char('x')]),
+                (Action::Legend, vec![Key::Char('l')]),
+                (Action::Focus, vec![Key::Enter]),
+                (Action::Exclude, vec![Key::Char('\\')]),
+                (Action::Bookmark, vec![Key::Char('m')]),
+                (Action::ShowBookmarks, vec![Key::Char('M')]),
+                (Action::Timeline, vec![Key::Char('t')]),
+                (Action::Regroup, vec![Key::Char('r')]),
+                (Action::Level, ('0'..='9').map(Key::Char).collect()),
+                (Action::Back, vec![Key::Char('q'), Key::Esc]),
+                (Action::Quit, vec![]),
+                (Action::Copy, vec![Key::Char('y')]),
+                (Action::Pick, vec![Key::Char('p')]),
+                (Action::Help, vec![Key::Char('?')]),
+            ],
+        }
+    }
+}
+
+impl TryFrom<HashMap<Action, Vec<String>>> for KeyBindings {
+    type Error = String;
+
+    fn try_from(overrides: HashMap<Action, Vec<String>>) -> Result<Self, Self::Error> {
+        KeyBindings::new(&overrides)
+    }
+}
+
+impl KeyBindings {
+    /// Replaces default keys of the configured actions, no key can be bound to more than one action.
+    pub fn new(overrides: &HashMap<Action, Vec<String>>) -> Result<KeyBindings, String> {
+        let mut result = KeyBindings::default();
+        for (action, codes) in &mut result.bindings {
+            if let Some(keys) = overrides.get(action) {
+                *codes = keys.iter().map(|k| parse_key(k)).collect::<Result<_, _>>()?;
+            }
+        }
+
+        let mut bound: HashMap<Key, Action> = HashMap::new();
+        for (action, codes) in &result.bindings {
+            for code in codes {
+                if let Some(other) = bound.insert(*code, *action) {
+                    if other != *action {
+                        return Err(format!("key {code} is bound to both {other:?} and {action:?}"));
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, codes)| codes.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Action, Vec<Key>)> {
+        self.bindings.iter()
+    }
+}
+
+fn parse_key(key: &str) -> Result<Key, String> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(c));
+    }
+    match key.to_lowercase().as_str() {
+        "space" => Ok(Key::Char(' ')),
+        "up" => Ok(Key::Up),
+        "down" => Ok(Key::Down),
+        "left" => Ok(Key::Left),
+        "right" => Ok(Key::Right),
+        "enter" => Ok(Key::Enter),
+        "esc" => Ok(Key::Esc),
+        "tab" => Ok(Key::Tab),
+        "backspace" => Ok(Key::Backspace),
+        "home" => Ok(Key::Home),
+        "end" => Ok(Key::End),
+        "pageup" => Ok(Key::PageUp),
+        "pagedown" => Ok(Key::PageDown),
+        name => name
+            .strip_prefix('f')
+            .and_then(|n| n.parse().ok())
+            .map(Key::F)
+            .ok_or_else(|| format!("unknown key {key}")),
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char(c) => write!(f, "{c}"),
+            Key::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keys::{Action, Key, KeyBindings};
+    use std::collections::HashMap;
+
+    #[test]
+    fn new_replaces_keys_of_configured_actions() {
+        let overrides = HashMap::from([
+            (Action::MoveUp, vec![String::from("k")]),
+            (Action::MoveDown, vec![String::from("j"), String::from("Down")]),
+        ]);
+        let bindings = KeyBindings::new(&overrides).expect("bindings are valid");
+        assert_eq!(Some(Action::MoveUp), bindings.action(Key::Char('k')));
+        assert_eq!(None, bindings.action(Key::Char('w')));
+        assert_eq!(Some(Action::MoveDown), bindings.action(Key::Down));
+        assert_eq!(Some(Action::Focus), bindings.action(Key::Enter));
+    }
+
+    #[test]
+    fn new_rejects_key_bound_to_several_actions() {
+        let overrides = HashMap::from([(Action::Focus, vec![String::from("q")])]);
+        assert!(KeyBindings::new(&overrides).is_err());
+    }
+
+    #[test]
+    fn new_rejects_unknown_key() {
+        let overrides = HashMap::from([(Action::Quit, vec![String::from("hyper")])]);
+        assert!(KeyBindings::new(&overrides).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+#![warn(clippy::all, clippy::pedantic)]
+// errors are reported as messages to the user, so they are described where they are created
+#![allow(clippy::must_use_candidate, clippy::missing_errors_doc, clippy::missing_panics_doc)]
+//! Reads JSON, YAML and CSV records into a dataframe of typed columns and groups its rows.
+//!
+//! ```
+//! use group::{read_dataframe, DataFrame, GroupOptions, GroupOrder, InputAttributeSpec, ReadOptions};
+//!
+//! let input = "{\"status\": 200}\n{\"status\": 500}\n{\"status\": 200}\n";
+//! let (df, _) = read_dataframe(input.as_bytes(), &[InputAttributeSpec::from("status")], &ReadOptions::default())?;
+//! let columns = [String::from("status")];
+//! let options = GroupOptions {
+//!     order: GroupOrder::SizeDesc,
+//!     ..GroupOptions::default()
+//! };
+//! let groups = df.group_by(&columns, &[], &options);
+//! assert_eq!(2, groups.len());
+//! assert_eq!(2, groups.group_size(0));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod configuration;
+pub mod io;
+pub mod keys;
+#[cfg(feature = "tui")]
+pub mod ui;
+mod utils;
+
+pub use configuration::InputSpec;
+pub use io::dataframe::{
+    Buckets, Column, ColumnValue, DataFrame, DataFrameFilterView, DataFrameGroupView, GroupOptions, GroupOrder, MaterializedDataFrame,
+};
+pub use io::input::{read_dataframe, InputAttributeSpec, ReadOptions, SkipPosition};
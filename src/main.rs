@@ -1,23 +1,275 @@
 #![warn(clippy::all, clippy::pedantic)]
 
-mod configuration;
-mod io;
-mod ui;
-mod utils;
-
+use notify::Watcher;
+use std::collections::HashMap;
 use std::fs;
-use structopt::StructOpt;
+use std::io::IsTerminal;
+use std::sync::mpsc;
+use string_error::into_err;
 
-use crate::io::input::read_dataframe;
-use crate::ui::show_dataframe;
+use group::configuration;
+use group::io::dataframe::{ColumnValue, DataFrame, GroupOptions, MaterializedDataFrame};
+use group::io::http;
+use group::io::input::{
+    build_dataframe, coerce_value, flatten_attributes, read_records, scan_dataframe, InputAttributeSpec, InputFormat, ReadOptions,
+    SkipPosition, FLATTEN_SAMPLE_SIZE, RECORD_ATTRIBUTE,
+};
+use group::io::markdown::to_markdown;
+use group::io::transform::{capture_column, split_column};
+use group::ui::{show_dataframe, to_ansi, to_html, DisplayOptions, Reloader};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = configuration::GroupOpts::from_args();
-    let spec = configuration::InputSpec::read_from_file(args.spec)?;
+    let args = configuration::GroupOpts::parse();
+    let mut spec = args.input_spec()?;
+
+    let (data, skipped) = load_dataframe(&args, &spec, std::io::stderr().is_terminal())?;
+    if skipped > 0 {
+        eprintln!("skipped {skipped} malformed lines");
+    }
+    if spec.group_by.is_empty() {
+        spec.group_by.extend(data.column_names().into_iter().take(1).cloned());
+    }
+    let column_formats: Vec<_> = spec.formats.iter().map(|(name, format)| (name.clone(), *format)).collect();
+    let buckets: Vec<_> = spec.buckets.iter().map(|(name, buckets)| (name.clone(), buckets.clone())).collect();
+    let representatives: Vec<_> = spec
+        .represent
+        .iter()
+        .map(|(name, representative)| (name.clone(), *representative))
+        .collect();
+    let grouping = GroupOptions {
+        distinct_columns: &spec.distinct,
+        buckets: &buckets,
+        representatives: &representatives,
+        coalesce_empty: spec.coalesce_empty,
+        order: args.sort_groups,
+        top: args.top_groups(),
+    };
+    let warnings = data.group_column_warnings(&spec.group_by, &grouping);
+    if args.strict && !warnings.is_empty() {
+        return Err(into_err(warnings.join(", ")));
+    }
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    let options = DisplayOptions {
+        max_string_width: args.max_string_width,
+        colors: args.colors_enabled(),
+        palette: args.palette,
+        max_colors: args.max_colors,
+        line_numbers: args.line_numbers,
+        zebra: args.zebra,
+        totals: args.totals,
+        column_spacing: args.column_spacing,
+        borders: args.borders,
+        null_value: &args.null_value,
+        true_value: &args.true_value,
+        false_value: &args.false_value,
+        sort_groups: spec.sort_groups.as_ref(),
+        column_order: &spec.display_order,
+        hidden_columns: &spec.hidden,
+        thousands_separator: args.thousands_separator.as_deref(),
+        decimals: args.decimals,
+        highlight: args.highlight,
+        wrap_around: !args.no_wrap,
+        align_decimals: args.align_decimals,
+        column_formats: &column_formats,
+    };
 
-    let reader = std::io::BufReader::new(fs::File::open(args.input)?);
-    let data = read_dataframe(reader, &spec.attrs, args.single)?;
-    show_dataframe(&data, &spec.group_by, &spec.show_in_grouped, &spec.timeline_column)?;
+    if args.export_md.is_some() || args.export_ansi.is_some() || args.export_html.is_some() {
+        let mut view = data.group_by(&spec.group_by, &spec.show_in_grouped, &grouping);
+        if let Some(order) = options.sort_groups {
+            view.sort_by_column(order);
+        }
+        if let Some(path) = &args.export_md {
+            write_export(path, &to_markdown(&view))?;
+        }
+        if let Some(path) = &args.export_ansi {
+            write_export(path, &to_ansi(&view, options))?;
+        }
+        if let Some(path) = &args.export_html {
+            write_export(path, &to_html(&view, options))?;
+        }
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let watcher = if args.watch {
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if res.is_ok_and(|e| e.kind.is_modify() || e.kind.is_create()) {
+                tx.send(()).ok();
+            }
+        })?;
+        for path in args.input.iter().filter(|path| !path.to_str().is_some_and(http::is_url)) {
+            watcher.watch(path, notify::RecursiveMode::NonRecursive)?;
+        }
+        Some(watcher)
+    } else {
+        None
+    };
+    let reloader = watcher.as_ref().map(|_| Reloader {
+        changes: rx,
+        load: Box::new(|| load_dataframe(&args, &spec, false).map(|(data, _)| data)),
+    });
+    // the terminal is restored at this point, so the record lands in the regular output
+    if let Some(picked) = show_dataframe(data, reloader.as_ref(), &spec, &grouping, options, args.confirm_quit)? {
+        println!("{picked}");
+    }
 
     Ok(())
 }
+
+/// Writes the exported table to the file, or to stdout for `-`.
+fn write_export(path: &std::path::Path, table: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if path.as_os_str() == "-" {
+        print!("{table}");
+        return Ok(());
+    }
+    fs::write(path, table).map_err(|e| into_err(format!("{}: {e}", path.display())))
+}
+
+/// Reads records of all input files and returns the dataframe with the number of skipped records.
+///
+/// With `show_progress` the share of every input read so far is printed to stderr.
+fn load_dataframe(
+    args: &configuration::GroupOpts,
+    spec: &configuration::InputSpec,
+    show_progress: bool,
+) -> Result<(MaterializedDataFrame, usize), Box<dyn std::error::Error>> {
+    let report_skip = |position: SkipPosition, e: &serde_json::Error| eprintln!("skipping {position}: {e}");
+    let read_options = |path: &std::path::Path, limit: Option<usize>| ReadOptions {
+        format: args.format.unwrap_or_else(|| InputFormat::from_path(path)),
+        single: args.single,
+        skip_errors: args.skip_errors,
+        limit,
+        no_datetime_inference: args.no_datetime_inference,
+        lenient: args.lenient,
+        progress: None,
+        on_skip: Some(&report_skip),
+    };
+    // inputs other than regular files such as pipes can only be read once, so their records are read up front
+    let mut piped = Vec::new();
+    for path in &args.input {
+        if let Some(url) = path.to_str().filter(|path| http::is_url(path)) {
+            let body = http::open(url, std::time::Duration::from_secs(args.http_timeout))?;
+            // the format of compressed inputs is given by the extension before .gz
+            let format_path = std::path::Path::new(url.strip_suffix(".gz").unwrap_or(url));
+            piped.push(Some(read_records(body, &read_options(format_path, args.limit)).map_err(with_path(path))?));
+            continue;
+        }
+        piped.push(if fs::metadata(path)?.is_file() {
+            None
+        } else {
+            let reader = std::io::BufReader::new(fs::File::open(path)?);
+            let progress = |read| print_progress(path, None, read);
+            let options = ReadOptions {
+                progress: show_progress.then_some(&progress as &dyn Fn(u64)),
+                ..read_options(path, args.limit)
+            };
+            Some(read_records(reader, &options).map_err(with_path(path))?)
+        });
+    }
+
+    let mut attrs = spec.attrs.clone();
+    if args.flatten {
+        // attributes are discovered in the first records, so only those are read before files are scanned
+        let mut sample = Vec::new();
+        for (path, records) in args.input.iter().zip(&piped) {
+            let limit = FLATTEN_SAMPLE_SIZE - sample.len();
+            if limit == 0 {
+                break;
+            }
+            if let Some((records, _)) = records {
+                sample.extend(records.iter().take(limit).cloned());
+            } else {
+                let reader = std::io::BufReader::new(fs::File::open(path)?);
+                let (mut records, _) = read_records(reader, &read_options(path, Some(limit))).map_err(with_path(path))?;
+                sample.append(&mut records);
+            }
+        }
+        for name in flatten_attributes(&sample) {
+            if !attrs.iter().any(|a| a.name == name) {
+                attrs.push(InputAttributeSpec::from(name));
+            }
+        }
+        // records without object leaves such as scalars are shown whole
+        if attrs.is_empty() {
+            attrs.push(InputAttributeSpec::from(RECORD_ATTRIBUTE));
+        }
+        spec.validate_with_attrs(&attrs)?;
+    }
+
+    let mut df = build_dataframe(Vec::new(), &attrs, &ReadOptions::default())?;
+    let mut skipped = 0;
+    for (path, records) in args.input.iter().zip(piped) {
+        let limit = args.limit.map(|limit| limit - df.len());
+        let (file_df, file_skipped) = if let Some((mut records, skipped)) = records {
+            records.truncate(limit.unwrap_or(usize::MAX));
+            (build_dataframe(records, &attrs, &read_options(path, limit)).map_err(with_path(path))?, skipped)
+        } else {
+            let size = fs::metadata(path)?.len();
+            let progress = |read| print_progress(path, Some(size), read);
+            let options = ReadOptions {
+                progress: show_progress.then_some(&progress as &dyn Fn(u64)),
+                ..read_options(path, limit)
+            };
+            scan_dataframe(path, &attrs, &options).map_err(with_path(path))?
+        };
+        df.append(file_df);
+        skipped += file_skipped;
+    }
+    if show_progress {
+        eprint!("\r\x1b[K");
+    }
+    for split in &spec.split {
+        split_column(&mut df, &split.column, &split.by, &split.into)?;
+    }
+    for capture in &spec.capture {
+        let regex = regex::Regex::new(&capture.regex)?;
+        capture_column(&mut df, &capture.column, &regex, capture.group, &capture.attribute(), !args.no_datetime_inference)?;
+    }
+    for derived in &spec.derived {
+        let column = derived.expr.evaluate(&derived.name, &df)?;
+        df.add_column(column)
+            .map_err(|e| into_err(format!("derived column {}: {e}", derived.name)))?;
+    }
+    df.remove_listed(&excluded_values(&spec.exclude, &attrs, !args.no_datetime_inference)?);
+    df.set_key_order(args.key_order);
+    Ok((df, skipped))
+}
+
+/// Converts the excluded values to the types of their columns, columns without an attribute are of any type.
+fn excluded_values(
+    excludes: &[configuration::ExcludeSpec],
+    attrs: &[InputAttributeSpec],
+    infer_datetimes: bool,
+) -> Result<HashMap<String, Vec<ColumnValue>>, Box<dyn std::error::Error>> {
+    let mut result: HashMap<String, Vec<ColumnValue>> = HashMap::new();
+    for exclude in excludes {
+        let attr = attrs
+            .iter()
+            .find(|a| a.name == exclude.column)
+            .cloned()
+            .unwrap_or_else(|| InputAttributeSpec::from(exclude.column.as_str()));
+        for value in &exclude.values {
+            let value =
+                coerce_value(value, &attr, infer_datetimes).map_err(|e| into_err(format!("excluded value of {}: {e}", exclude.column)))?;
+            result.entry(exclude.column.clone()).or_default().push(value);
+        }
+    }
+    Ok(result)
+}
+
+/// Prints the progress of reading the input over the previous one, inputs of unknown size show the number of bytes read.
+#[allow(clippy::cast_precision_loss)]
+fn print_progress(path: &std::path::Path, size: Option<u64>, read: u64) {
+    let progress = match size {
+        Some(size) if size > 0 => format!("{}%", read.min(size) * 100 / size),
+        _ => format!("{:.1} MiB", read as f64 / f64::from(1 << 20)),
+    };
+    eprint!("\rloading {}: {progress}\x1b[K", path.display());
+}
+
+/// Prefixes errors of reading the input with its path.
+fn with_path(path: &std::path::Path) -> impl Fn(Box<dyn std::error::Error>) -> Box<dyn std::error::Error> + '_ {
+    move |e| into_err(format!("{}: {}", path.display(), e))
+}
@@ -1,19 +1,60 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use tui::backend;
 use tui::layout;
 use tui::Frame;
 
+use crate::configuration::InputSpec;
 use crate::io::dataframe;
+use crate::io::dataframe::DataFrame;
+use crate::ui::bar;
 use crate::ui::card;
 use crate::ui::footer;
+use crate::ui::help;
+use crate::ui::keys;
+use crate::ui::legend;
+use crate::ui::picker;
 use crate::ui::table;
 use crate::ui::timeline;
+use crate::ui::value;
+use crate::ui::DisplayOptions;
 use std::collections::VecDeque;
 
 struct AppState<'a> {
     table_view_model: table::ViewModel<'a>,
     mode: AppMode,
+    /// Description of the filter applied to reach the state
+    filter: Option<String>,
+    /// How the state was entered from the previous one
+    drill: Drill,
+    /// Selected rows of the states entered from this one by their filter, restored when they are entered again
+    child_selections: HashMap<String, usize>,
+}
+
+/// Way of reaching a level of the drill path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Drill {
+    /// The first level showing the groups
+    Groups,
+    /// Rows of the selected group
+    Focus,
+    /// Rows outside of the selected group
+    Exclude,
+    /// Bookmarked rows
+    Bookmarks,
+}
+
+/// View shown over the table, it takes the next key press.
+pub enum Overlay {
+    Help,
+    /// Full value of the selected cell
+    Value,
+    /// Colors of the values of the selected column
+    Legend,
+    /// Columns to choose the group columns from
+    Picker(picker::ColumnPicker),
+    /// Question whether to quit, shown in the footer and answered by the next key press
+    ConfirmQuit,
 }
 
 enum AppMode {
@@ -32,7 +73,17 @@ impl AppMode {
 
 pub struct ViewModel<'a> {
     source_df: &'a dataframe::MaterializedDataFrame,
+    group_columns: &'a [String],
+    /// Columns identifying the rows of a group
+    filter_columns: Vec<&'a String>,
     state: VecDeque<AppState<'a>>,
+    options: DisplayOptions<'a>,
+    bindings: &'a keys::KeyBindings,
+    pub overlay: Option<Overlay>,
+    /// Message shown in the footer until the next key press
+    pub status: Option<String>,
+    /// Indices of bookmarked rows in the source dataframe
+    pub bookmarks: HashSet<usize>,
 }
 
 impl<'a> ViewModel<'a> {
@@ -40,19 +91,47 @@ impl<'a> ViewModel<'a> {
         source_df: &'a dataframe::MaterializedDataFrame,
         group_columns: &'a [String],
         show_in_grouped_mode: &'a [String],
-        timeline_column: &'a Option<String>,
+        spec: &'a InputSpec,
+        grouping: &dataframe::GroupOptions<'_>,
+        options: DisplayOptions<'a>,
     ) -> ViewModel<'a> {
-        let df = source_df.group_by(group_columns, show_in_grouped_mode);
+        let mut df = source_df.group_by(group_columns, show_in_grouped_mode, grouping);
 
-        let timeline_column = timeline_column
-            .as_ref()
-            .map(|c| timeline::create_timeline_column(source_df, &df, c, table::TIMELINE_WIDTH));
+        if let Some(order) = options.sort_groups {
+            df.sort_by_column(order);
+        }
+
+        let mut charts = Vec::new();
+        if let Some(bar) = &spec.bar {
+            charts.push(table::ChartColumn {
+                cells: bar::create_bar_column(source_df, &df, &bar.column, bar.width),
+                width: bar.width,
+                timeline: false,
+            });
+        }
+        if let Some(c) = &spec.timeline_column {
+            charts.push(table::ChartColumn {
+                cells: timeline::create_timeline_column(source_df, &df, c, table::TIMELINE_WIDTH),
+                width: table::TIMELINE_WIDTH,
+                timeline: true,
+            });
+        }
         ViewModel {
             source_df,
+            group_columns,
+            filter_columns: group_columns.iter().chain(show_in_grouped_mode).collect(),
             state: VecDeque::from([AppState {
-                table_view_model: table::ViewModel::new(Box::from(df), timeline_column),
+                table_view_model: table::ViewModel::new(Box::from(df), group_columns, charts, options),
                 mode: AppMode::Grouped,
+                filter: None,
+                drill: Drill::Groups,
+                child_selections: HashMap::new(),
             }]),
+            options,
+            bindings: &spec.keys,
+            overlay: None,
+            status: None,
+            bookmarks: HashSet::new(),
         }
     }
 
@@ -60,30 +139,198 @@ impl<'a> ViewModel<'a> {
         self.get_current_state_mut().table_view_model.move_selected(up);
     }
 
+    pub fn page(&mut self, up: bool) {
+        self.get_current_state_mut().table_view_model.page(up);
+    }
+
+    pub fn move_selected_column(&mut self, left: bool) {
+        self.get_current_state_mut().table_view_model.move_selected_column(left);
+    }
+
+    /// Returns the name and the full value of the selected cell.
+    fn get_selected_value(&self) -> Option<(&String, &dataframe::ColumnValue)> {
+        let table_view_model = &self.get_current_state().table_view_model;
+        if table_view_model.df.is_empty() {
+            return None;
+        }
+        let name = table_view_model.get_selected_column_name();
+        Some((name, table_view_model.df.get((name, table_view_model.selected))))
+    }
+
+    /// Shows the columns of the source with the group columns checked.
+    pub fn open_column_picker(&mut self) {
+        self.overlay = Some(Overlay::Picker(picker::ColumnPicker::new(&self.source_df.column_names(), self.group_columns)));
+    }
+
+    /// Shows the legend of the selected column, unless values are not colored.
+    pub fn show_legend(&mut self) {
+        if self.options.colors {
+            self.overlay = Some(Overlay::Legend);
+        } else {
+            self.status = Some(String::from("colors are off"));
+        }
+    }
+
+    pub fn jump_to(&mut self, index: usize) {
+        self.get_current_state_mut().table_view_model.jump_to(index);
+    }
+
+    /// Highlights the text in the current table and selects the next row with it, an empty text clears the highlight.
+    pub fn search(&mut self, query: &str) {
+        if !self.get_current_state_mut().table_view_model.search(query) {
+            self.status = Some(format!("no rows with {query}"));
+        }
+    }
+
+    /// Selects the clicked row, clicking the selected row focuses it.
+    pub fn click(&mut self, column: u16, row: u16) {
+        let table_view_model = &mut self.get_current_state_mut().table_view_model;
+        match table_view_model.row_at(column, row) {
+            Some(index) if index == table_view_model.selected => self.focus(),
+            Some(index) => table_view_model.set_selected(index),
+            None => {}
+        }
+    }
+
     pub fn focus(&mut self) {
-        let state = self.get_current_state_mut();
+        // borrow the state directly to keep the filter columns accessible
+        let state = self.state.back_mut().expect("app state cannot be empty");
+        if state.table_view_model.df.is_empty() {
+            return;
+        }
         match &mut state.mode {
-            AppMode::Grouped => {
-                let selected = state.table_view_model.selected_row();
-                let filter: HashMap<String, dataframe::ColumnValue> = state
-                    .table_view_model
-                    .df
-                    .column_names()
-                    .into_iter()
-                    .enumerate()
-                    .map(|(i, c)| (c.clone(), selected[i].clone()))
-                    .collect();
-
-                let df = self.source_df.filter(&filter);
-                self.state.push_back(AppState {
-                    table_view_model: table::ViewModel::new(Box::from(df), None),
-                    mode: AppMode::Filtered(false),
-                });
-            }
+            AppMode::Grouped => self.drill_down(Drill::Focus),
             AppMode::Filtered(focused) => {
                 *focused = !*focused;
             }
+        }
+    }
+
+    /// Shows rows of every group except the selected one.
+    pub fn exclude(&mut self) {
+        let state = self.get_current_state();
+        if matches!(state.mode, AppMode::Grouped) && !state.table_view_model.df.is_empty() {
+            self.drill_down(Drill::Exclude);
+        }
+    }
+
+    /// Bookmarks the selected row, or removes its bookmark.
+    pub fn toggle_bookmark(&mut self) {
+        let state = self.get_current_state();
+        if matches!(state.mode, AppMode::Grouped) {
+            self.status = Some(String::from("only rows of a filtered view can be bookmarked"));
+            return;
+        }
+        if state.table_view_model.df.is_empty() {
+            return;
+        }
+        let index = state.table_view_model.df.source_index(state.table_view_model.selected);
+        self.status = Some(String::from(if self.bookmarks.remove(&index) {
+            "removed bookmark"
+        } else {
+            self.bookmarks.insert(index);
+            "bookmarked row"
+        }));
+    }
+
+    /// Shows or hides the timeline column of the groups, the data columns get its width while it is hidden.
+    pub fn toggle_timeline(&mut self) {
+        let groups = &mut self.get_grouped_state_mut().table_view_model;
+        if !groups.has_timeline() {
+            self.status = Some(String::from("no timeline column in the spec"));
+            return;
+        }
+        groups.show_timeline = !groups.show_timeline;
+    }
+
+    /// Returns false if the timeline column of the groups is hidden.
+    pub fn timeline_shown(&self) -> bool {
+        self.state.front().is_none_or(|state| state.table_view_model.show_timeline)
+    }
+
+    /// Shows the bookmarked rows, or goes back if they are already shown.
+    pub fn show_bookmarks(&mut self) {
+        if self.get_current_state().drill == Drill::Bookmarks {
+            self.pop_state();
+            return;
+        }
+        if self.bookmarks.is_empty() {
+            self.status = Some(String::from("no bookmarked rows"));
+            return;
+        }
+        self.push_state(AppState {
+            table_view_model: table::ViewModel::new(
+                Box::from(self.source_df.select(&self.bookmarks)),
+                self.group_columns,
+                Vec::new(),
+                self.options,
+            ),
+            mode: AppMode::Filtered(false),
+            filter: Some(String::from("★ bookmarks")),
+            drill: Drill::Bookmarks,
+            child_selections: HashMap::new(),
+        });
+    }
+
+    fn drill_down(&mut self, drill: Drill) {
+        let excluded = drill == Drill::Exclude;
+        let table_view_model = &self.get_current_state().table_view_model;
+        let filter: HashMap<&str, &dataframe::ColumnValue> = table_view_model
+            .df
+            .named_row(table_view_model.selected)
+            .into_iter()
+            .filter(|(name, _)| self.filter_columns.contains(name))
+            .map(|(name, value)| (name.as_str(), value))
+            .collect();
+        let description = describe_filter(self.group_columns, &filter, excluded);
+
+        // the (other) group has no single key, so its rows are selected by index
+        let rolled_up: Option<HashSet<usize>> = table_view_model
+            .df
+            .rolled_up_rows(table_view_model.selected)
+            .map(|rows| rows.iter().copied().collect());
+        let df = match (rolled_up, excluded) {
+            (Some(rows), true) => self
+                .source_df
+                .select(&(0..self.source_df.len()).filter(|i| !rows.contains(i)).collect()),
+            (Some(rows), false) => self.source_df.select(&rows),
+            (None, true) => self.source_df.exclude(&filter),
+            (None, false) => self.source_df.filter(&filter),
         };
+        self.push_state(AppState {
+            table_view_model: table::ViewModel::new(Box::from(df), self.group_columns, Vec::new(), self.options),
+            mode: AppMode::Filtered(false),
+            filter: Some(description),
+            drill,
+            child_selections: HashMap::new(),
+        });
+    }
+
+    /// Enters the state, selecting the row that was selected when it was left the last time.
+    fn push_state(&mut self, mut state: AppState<'a>) {
+        let remembered = state.filter.as_ref().and_then(|f| self.get_current_state().child_selections.get(f));
+        if let Some(selected) = remembered {
+            let last = state.table_view_model.df.len().saturating_sub(1);
+            state.table_view_model.set_selected((*selected).min(last));
+        }
+        self.state.push_back(state);
+    }
+
+    /// Leaves the current state, its selected row is remembered by the previous state.
+    fn pop_state(&mut self) {
+        if self.state.len() < 2 {
+            return;
+        }
+        if let Some(AppState {
+            table_view_model,
+            filter: Some(filter),
+            ..
+        }) = self.state.pop_back()
+        {
+            self.get_current_state_mut()
+                .child_selections
+                .insert(filter, table_view_model.selected);
+        }
     }
 
     pub fn back(&mut self) -> bool {
@@ -94,19 +341,72 @@ impl<'a> ViewModel<'a> {
                 if *focused {
                     *focused = false;
                 } else {
-                    self.state.pop_back();
+                    self.pop_state();
                 }
                 true
             }
         }
     }
 
+    /// Goes back to the drill level, the first level shows the groups.
+    /// Levels deeper than the current one are left alone with a status message.
+    pub fn back_to_level(&mut self, level: usize) {
+        if level >= self.state.len() {
+            self.status = Some(format!("no level {level}, the deepest level is {}", self.state.len() - 1));
+            return;
+        }
+        while self.state.len() > level + 1 {
+            self.pop_state();
+        }
+        if let AppMode::Filtered(focused) = &mut self.get_current_state_mut().mode {
+            *focused = false;
+        }
+    }
+
+    /// Returns the raw record shown in the card, if any.
+    pub fn focused_raw(&self) -> Option<&String> {
+        let state = self.get_current_state();
+        match state.mode {
+            AppMode::Filtered(true) => Some(state.table_view_model.df.raw(state.table_view_model.selected)),
+            _ => None,
+        }
+    }
+
+    /// Returns the selected row, whether the card is shown and how the level was entered for every level of the drill path.
+    pub fn drill_path(&self) -> Vec<(usize, bool, Drill)> {
+        self.state
+            .iter()
+            .map(|s| (s.table_view_model.selected, matches!(s.mode, AppMode::Filtered(true)), s.drill))
+            .collect()
+    }
+
+    /// Repeats a drill path returned by `drill_path`, selections are clamped to the current data.
+    pub fn restore_drill_path(&mut self, path: &[(usize, bool, Drill)]) {
+        for (selected, focused, drill) in path {
+            match drill {
+                Drill::Groups => {}
+                Drill::Focus => self.focus(),
+                Drill::Exclude => self.exclude(),
+                Drill::Bookmarks => self.show_bookmarks(),
+            }
+            let table_view_model = &mut self.get_current_state_mut().table_view_model;
+            let last = table_view_model.df.len().saturating_sub(1);
+            table_view_model.set_selected((*selected).min(last));
+            if *focused {
+                self.focus();
+            }
+        }
+    }
+
     fn get_current_state(&self) -> &AppState<'a> {
         self.state.back().expect("app state cannot be empty")
     }
     fn get_current_state_mut(&mut self) -> &mut AppState<'a> {
         self.state.back_mut().expect("app state cannot be empty")
     }
+    fn get_grouped_state_mut(&mut self) -> &mut AppState<'a> {
+        self.state.front_mut().expect("app state cannot be empty")
+    }
 }
 
 pub struct View<'a: 'b, 'b> {
@@ -123,16 +423,52 @@ impl<'a: 'b, 'b> View<'a, 'b> {
         let current_state = self.view_model.get_current_state();
         let row_count = current_state.table_view_model.df.len();
         let selected = current_state.table_view_model.selected;
-        let footer_view = footer::Footer::new(current_state.mode.get_name(), selected + 1, row_count);
+        let colors = self.view_model.options.colors;
+        let table_width = frame
+            .size()
+            .width
+            .saturating_sub(if self.view_model.options.borders { 2 } else { 0 });
+        let footer_view = footer::Footer::new(
+            current_state.mode.get_name(),
+            &self.view_model.state.iter().filter_map(|s| s.filter.as_deref()).collect::<Vec<_>>(),
+            selected + 1,
+            row_count,
+            current_state.table_view_model.get_column_range(table_width),
+            self.view_model.bookmarks.len(),
+            self.view_model.status.as_deref(),
+            colors,
+        );
         let card_view = if let AppMode::Filtered(focused) = &current_state.mode {
             if *focused {
-                Some(card::View::new(current_state.table_view_model.df.raw(selected)))
+                let width = frame.size().width;
+                Some(
+                    card::View::new(current_state.table_view_model.df.raw(selected), width, colors)
+                        .unwrap_or_else(|e| card::View::error(&e.to_string(), width, colors)),
+                )
             } else {
                 None
             }
         } else {
             None
         };
+        let overlay = &self.view_model.overlay;
+        let help_view = matches!(overlay, Some(Overlay::Help)).then(|| help::view(self.view_model.bindings));
+        let value_view = if matches!(overlay, Some(Overlay::Value)) {
+            self.view_model
+                .get_selected_value()
+                .map(|(name, value)| value::View::new(name, value))
+        } else {
+            None
+        };
+        let legend_view = matches!(overlay, Some(Overlay::Legend)).then(|| {
+            let table_view_model = &self.view_model.get_current_state().table_view_model;
+            let column = table_view_model.df.column(table_view_model.get_selected_column_name());
+            legend::view(column, self.view_model.options)
+        });
+        let picker_view = match overlay {
+            Some(Overlay::Picker(picker)) => Some(picker::view(picker)),
+            _ => None,
+        };
         let current_state = &mut self.view_model.get_current_state_mut().table_view_model;
         let table_view = table::View::new(current_state);
 
@@ -166,14 +502,126 @@ impl<'a: 'b, 'b> View<'a, 'b> {
         };
         footer_view.render(frame, chunks[1]);
         table_view.render(frame, table_size);
+        if let Some(value_view) = value_view {
+            value_view.render(frame, size);
+        }
+        if let Some(legend_view) = legend_view {
+            legend_view.render(frame, size);
+        }
+        if let Some(picker_view) = picker_view {
+            picker_view.render(frame, size);
+        }
+        if let Some(help_view) = help_view {
+            help_view.render(frame, size);
+        }
+    }
+}
+
+/// Describes the filter of group columns as `a = x, b = y`, or its negation if the group is excluded.
+fn describe_filter(group_columns: &[String], filter: &HashMap<&str, &dataframe::ColumnValue>, excluded: bool) -> String {
+    let operator = if excluded && group_columns.len() == 1 { "!=" } else { "=" };
+    let description = group_columns
+        .iter()
+        .filter_map(|c| filter.get(c.as_str()).map(|v| format!("{c} {operator} {v}")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if excluded && group_columns.len() > 1 {
+        format!("not ({description})")
+    } else {
+        description
     }
 }
 
 #[allow(clippy::cast_possible_truncation)]
 fn usize_to_u16(v: usize) -> u16 {
-    if v < std::u16::MAX as usize {
+    if v < u16::MAX as usize {
         v as u16
     } else {
-        std::u16::MAX
+        u16::MAX
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::configuration::InputSpec;
+    use crate::io::dataframe::{Column, ColumnValue, GroupOptions, MaterializedDataFrame};
+    use crate::ui::app::{Drill, ViewModel};
+    use crate::ui::DisplayOptions;
+
+    /// Dataframe of the status codes with a spec grouping them by status.
+    fn statuses(values: &[i64]) -> (MaterializedDataFrame, InputSpec) {
+        let column = Column {
+            name: String::from("s"),
+            values: values.iter().copied().map(ColumnValue::Integer).collect(),
+        };
+        let records = values.iter().map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let spec = InputSpec {
+            group_by: vec![String::from("s")],
+            ..InputSpec::default()
+        };
+        (df, spec)
+    }
+
+    fn view_model<'a>(df: &'a MaterializedDataFrame, spec: &'a InputSpec) -> ViewModel<'a> {
+        ViewModel::new(df, &spec.group_by, &[], spec, &GroupOptions::default(), DisplayOptions::DEFAULT)
+    }
+
+    #[test]
+    fn back_to_level_pops_filters_above_level() {
+        let (df, spec) = statuses(&[200, 500, 200]);
+        let mut view_model = view_model(&df, &spec);
+
+        view_model.exclude();
+        assert_eq!(Some("s != 200"), view_model.get_current_state().filter.as_deref());
+        view_model.focus();
+        view_model.back_to_level(2);
+        assert_eq!(Some("no level 2, the deepest level is 1"), view_model.status.as_deref());
+        assert_eq!(vec![(0, false, Drill::Groups), (0, true, Drill::Exclude)], view_model.drill_path());
+        view_model.back_to_level(1);
+        assert_eq!(vec![(0, false, Drill::Groups), (0, false, Drill::Exclude)], view_model.drill_path());
+        view_model.back_to_level(0);
+        assert_eq!(vec![(0, false, Drill::Groups)], view_model.drill_path());
+    }
+
+    #[test]
+    fn bookmarks_keep_source_rows_across_views() {
+        let (df, spec) = statuses(&[200, 500, 200]);
+        let mut view_model = view_model(&df, &spec);
+
+        view_model.toggle_bookmark();
+        assert!(view_model.bookmarks.is_empty());
+        view_model.focus();
+        view_model.move_selected(false);
+        view_model.toggle_bookmark();
+        view_model.back();
+        view_model.exclude();
+        view_model.toggle_bookmark();
+        assert_eq!([1, 2].into_iter().collect::<std::collections::HashSet<_>>(), view_model.bookmarks);
+
+        view_model.show_bookmarks();
+        let table_view_model = &view_model.get_current_state().table_view_model;
+        assert_eq!(vec![1, 2], (0..2).map(|i| table_view_model.df.source_index(i)).collect::<Vec<_>>());
+        view_model.show_bookmarks();
+        assert_eq!(Drill::Exclude, view_model.get_current_state().drill);
+    }
+
+    #[test]
+    fn focus_restores_selection_of_group_entered_again() {
+        let (df, spec) = statuses(&[200, 500, 200, 200]);
+        let mut view_model = view_model(&df, &spec);
+
+        view_model.focus();
+        view_model.move_selected(false);
+        view_model.move_selected(false);
+        view_model.back();
+        view_model.move_selected(false);
+        view_model.focus();
+        assert_eq!(vec![(1, false, Drill::Groups), (0, false, Drill::Focus)], view_model.drill_path());
+
+        view_model.back_to_level(0);
+        view_model.move_selected(true);
+        view_model.focus();
+        assert_eq!(vec![(0, false, Drill::Groups), (2, false, Drill::Focus)], view_model.drill_path());
     }
 }
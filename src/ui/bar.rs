@@ -0,0 +1,59 @@
+use crate::io::dataframe::{DataFrame, DataFrameGroupView, MaterializedDataFrame};
+
+/// Bar of the mean value of the column in every group.
+pub fn create_bar_column(source_df: &MaterializedDataFrame, df: &DataFrameGroupView<'_>, column_name: &str, width: u16) -> Vec<String> {
+    let Some(column) = source_df.get_column(column_name) else {
+        return vec![String::new(); df.len()];
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let means: Vec<f64> = (0..df.len())
+        .map(|i| {
+            let numbers: Vec<f64> = df.group_indices(i).iter().filter_map(|j| column[*j].as_f64()).collect();
+            if numbers.is_empty() {
+                0.0
+            } else {
+                numbers.iter().sum::<f64>() / numbers.len() as f64
+            }
+        })
+        .collect();
+    create_bars(&means, width)
+}
+
+/// Draws horizontal bars scaled so that the largest value takes the whole width, non-positive values are empty.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn create_bars(values: &[f64], width: u16) -> Vec<String> {
+    const RAMP: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max <= 0.0 || *v <= 0.0 {
+                return String::new();
+            }
+            let eighths = (v / max * f64::from(width) * 8.0).round() as usize;
+            let mut bar = "█".repeat(eighths / 8);
+            let remainder = eighths % 8;
+            if remainder > 0 {
+                bar.push(RAMP[remainder - 1]);
+            }
+            bar
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::bar::create_bars;
+
+    #[test]
+    fn create_bars_scales_to_largest_value() {
+        assert_eq!(vec!["████", "██", "█▎", ""], create_bars(&[1.0, 0.5, 0.3, 0.0], 4));
+    }
+
+    #[test]
+    fn create_bars_leaves_non_positive_values_empty() {
+        assert_eq!(vec!["", ""], create_bars(&[-1.0, 0.0], 4));
+    }
+}
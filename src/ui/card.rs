@@ -11,10 +11,34 @@ pub struct View<'a> {
 }
 
 impl<'a> View<'a> {
-    pub fn new<'b>(txt: &'b str) -> View<'a> {
-        let obj: serde_json::Value = serde_json::from_str(txt).expect("failed to parse");
-        let colored = to_colored_yaml(&obj);
-        let text_element = text::Text::from(colored);
+    /// Creates the card of the raw record, fails if the record is not valid JSON.
+    pub fn new<'b>(txt: &'b str, width: u16, colors: bool) -> Result<View<'a>, serde_json::Error> {
+        let obj: serde_json::Value = serde_json::from_str(txt)?;
+        let mut colored: Vec<text::Spans> = to_colored_yaml(&obj)
+            .into_iter()
+            .flat_map(|line| wrap_line(line, width.into()))
+            .collect();
+        if !colors {
+            for span in colored.iter_mut().flat_map(|line| line.0.iter_mut()) {
+                span.style = style::Style::default();
+            }
+        }
+        Ok(View::from_lines(colored))
+    }
+
+    /// Creates the card shown in place of a record that can't be displayed.
+    pub fn error(message: &str, width: u16, colors: bool) -> View<'a> {
+        let style = if colors {
+            style::Style::default().fg(style::Color::Red)
+        } else {
+            style::Style::default()
+        };
+        let line = text::Spans::from(text::Span::styled(format!("failed to display record: {message}"), style));
+        View::from_lines(wrap_line(line, width.into()))
+    }
+
+    fn from_lines(lines: Vec<text::Spans<'a>>) -> View<'a> {
+        let text_element = text::Text::from(lines);
         let height = text_element.height() + 1;
         let para = widgets::Paragraph::new(text_element).block(widgets::Block::default().borders(widgets::Borders::TOP));
 
@@ -30,8 +54,42 @@ impl<'a> View<'a> {
     }
 }
 
+const PADDING_INCR: usize = 2;
+
+/// Splits a line longer than the width, continuation lines are indented one level deeper than the line.
+fn wrap_line(line: text::Spans<'_>, width: usize) -> Vec<text::Spans<'_>> {
+    if line.width() <= width || width == 0 {
+        return vec![line];
+    }
+    let padding = line.0.first().map_or(0, |s| s.content.chars().take_while(|c| *c == ' ').count());
+    let indent = (padding + PADDING_INCR).min(width / 2);
+
+    let mut lines = Vec::new();
+    let mut current: Vec<text::Span> = Vec::new();
+    let mut current_width = 0;
+    for span in line.0 {
+        let mut part = String::new();
+        for c in span.content.chars() {
+            if current_width == width {
+                if !part.is_empty() {
+                    current.push(text::Span::styled(std::mem::take(&mut part), span.style));
+                }
+                lines.push(text::Spans::from(std::mem::take(&mut current)));
+                current.push(text::Span::from(" ".repeat(indent)));
+                current_width = indent;
+            }
+            part.push(c);
+            current_width += 1;
+        }
+        if !part.is_empty() {
+            current.push(text::Span::styled(part, span.style));
+        }
+    }
+    lines.push(text::Spans::from(current));
+    lines
+}
+
 fn to_colored_yaml<'a>(obj: &serde_json::Value) -> Vec<text::Spans<'a>> {
-    const PADDING_INCR: usize = 2;
     const KEY_COLOR: style::Color = style::Color::Red;
     const STRING_COLOR: style::Color = style::Color::LightGreen;
     const LITERAL_COLOR: style::Color = style::Color::LightCyan;
@@ -54,7 +112,7 @@ fn to_colored_yaml<'a>(obj: &serde_json::Value) -> Vec<text::Spans<'a>> {
                 )],
                 false,
             ),
-            serde_json::Value::Number(v) => (vec![new_line!(padding, colored_text(format!("{}", v), LITERAL_COLOR))], false),
+            serde_json::Value::Number(v) => (vec![new_line!(padding, colored_text(format!("{v}"), LITERAL_COLOR))], false),
             serde_json::Value::String(v) => {
                 if v.is_empty() {
                     (vec![new_line!(padding, colored_text_ref("\"\"", STRING_COLOR))], false)
@@ -114,3 +172,31 @@ fn colored_text_ref<'a>(txt: &'static str, color: style::Color) -> text::Span<'a
 fn colored_text<'a>(txt: String, color: style::Color) -> text::Span<'a> {
     text::Span::styled(txt, style::Style::default().fg(color))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::ui::card::{wrap_line, View};
+    use tui::text;
+
+    #[test]
+    fn wrap_line_indents_continuation_lines() {
+        let line = text::Spans::from(vec![text::Span::from("  "), text::Span::from("url: "), text::Span::from("abcdefghij")]);
+        let wrapped: Vec<String> = wrap_line(line, 8)
+            .iter()
+            .map(|l| l.0.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(vec!["  url: a", "    bcde", "    fghi", "    j"], wrapped);
+    }
+
+    #[test]
+    fn wrap_line_keeps_short_lines() {
+        let line = text::Spans::from("short");
+        assert_eq!(1, wrap_line(line, 8).len());
+    }
+
+    #[test]
+    fn new_fails_on_malformed_record() {
+        assert!(View::new("{\"a\": ", 80, true).is_err());
+        assert!(View::new("{\"a\": 1}", 80, true).is_ok());
+    }
+}
@@ -1,22 +1,140 @@
-use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
 use crate::io::dataframe::{Column, ColumnValue};
 use crate::max;
+use crate::ui::DisplayOptions;
 use tui::style;
 
-type Colorizer = fn(value: &ColumnValue) -> style::Color;
+type Colorizer = Box<dyn Fn(&ColumnValue) -> style::Color>;
 
-pub fn select(col: &Column) -> Colorizer {
-    const MAX_COLORS: usize = 16;
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Palette {
+    Rgb,
+    ColorblindSafe,
+}
+
+impl std::str::FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rgb" => Ok(Palette::Rgb),
+            "cb-safe" => Ok(Palette::ColorblindSafe),
+            _ => Err(format!("unknown palette {s}")),
+        }
+    }
+}
+
+/// Picks the colorizer of the column.
+///
+/// Up to `max_colors` distinct values get colors of their own, taken from the palette. Numeric columns with
+/// more values are colored on a gradient, other columns reuse colors of the palette, so some values collide.
+/// Raising the limit colors more values apart, but the colors get closer and harder to tell apart.
+pub fn select(col: &Column, options: DisplayOptions<'_>) -> Colorizer {
+    if !options.colors {
+        return Box::new(colorize_default);
+    }
 
     let mut unique_values = col.unique();
     unique_values.remove(&ColumnValue::None);
-    if (2..=MAX_COLORS).contains(&unique_values.len()) {
-        colorize_rgb
+    if (2..=options.max_colors).contains(&unique_values.len()) {
+        match options.palette {
+            Palette::Rgb => colorize_hues(col),
+            Palette::ColorblindSafe => Box::new(colorize_palette),
+        }
+    } else if col.is_numeric() {
+        colorize_gradient(col)
+    } else if unique_values.len() > options.max_colors {
+        match options.palette {
+            Palette::Rgb => Box::new(colorize_rgb),
+            Palette::ColorblindSafe => Box::new(colorize_palette),
+        }
     } else {
-        colorize_static
+        Box::new(colorize_static)
+    }
+}
+
+/// Spreads distinct values evenly around the hue circle in the order of their first occurrence.
+#[allow(clippy::cast_precision_loss)]
+fn colorize_hues(col: &Column) -> Colorizer {
+    let mut values: Vec<&ColumnValue> = Vec::new();
+    for value in &col.values {
+        if *value != ColumnValue::None && !values.contains(&value) {
+            values.push(value);
+        }
     }
+    let count = values.len() as f64;
+    let colors: HashMap<ColumnValue, style::Color> = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (v.clone(), hsl_to_rgb(360.0 * i as f64 / count, 0.7, 0.65)))
+        .collect();
+
+    Box::new(move |value| colors.get(value).copied().unwrap_or_else(|| colorize_static(value)))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> style::Color {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let second = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue {
+        h if h < 60.0 => (chroma, second, 0.0),
+        h if h < 120.0 => (second, chroma, 0.0),
+        h if h < 180.0 => (0.0, chroma, second),
+        h if h < 240.0 => (0.0, second, chroma),
+        h if h < 300.0 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let offset = lightness - chroma / 2.0;
+    let channel = |c: f64| ((c + offset) * 255.0).round() as u8;
+
+    style::Color::Rgb(channel(red), channel(green), channel(blue))
+}
+
+/// FNV-1a hasher, unlike `DefaultHasher` it gives the same hashes in every build so that values keep their colors.
+///
+/// Integers are hashed as little-endian 64-bit numbers to give the same hashes on every platform as well.
+struct StableHasher(u64);
+
+impl StableHasher {
+    /// Offset basis of 64-bit FNV-1a, the seed of every hash
+    const SEED: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(StableHasher::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write_u64(n.into());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write_u64(n.into());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+}
+
+fn hash_value(value: &ColumnValue) -> u64 {
+    let mut hasher = StableHasher(StableHasher::SEED);
+    value.hash(&mut hasher);
+    hasher.finish()
 }
 
 #[allow(clippy::cast_possible_truncation)]
@@ -29,9 +147,7 @@ fn colorize_rgb(value: &ColumnValue) -> style::Color {
         };
     }
 
-    let mut hasher = DefaultHasher::new();
-    value.hash(&mut hasher);
-    let hash = hasher.finish();
+    let hash = hash_value(value);
 
     let r = intensify!(max!(hash as u8, (hash >> 8) as u8, (hash >> 16) as u8));
     let g = intensify!(max!((hash >> 24) as u8, (hash >> 32) as u8, (hash >> 40) as u8));
@@ -40,6 +156,133 @@ fn colorize_rgb(value: &ColumnValue) -> style::Color {
     style::Color::Rgb(r, g, b)
 }
 
+#[allow(clippy::cast_possible_truncation)]
+fn colorize_palette(value: &ColumnValue) -> style::Color {
+    // Okabe-Ito palette with black replaced by grey to stay visible on dark terminals
+    const PALETTE: [style::Color; 8] = [
+        style::Color::Rgb(0xE6, 0x9F, 0x00),
+        style::Color::Rgb(0x56, 0xB4, 0xE9),
+        style::Color::Rgb(0x00, 0x9E, 0x73),
+        style::Color::Rgb(0xF0, 0xE4, 0x42),
+        style::Color::Rgb(0x00, 0x72, 0xB2),
+        style::Color::Rgb(0xD5, 0x5E, 0x00),
+        style::Color::Rgb(0xCC, 0x79, 0xA7),
+        style::Color::Rgb(0xBB, 0xBB, 0xBB),
+    ];
+
+    PALETTE[(hash_value(value) % PALETTE.len() as u64) as usize]
+}
+
+fn colorize_gradient(col: &Column) -> Colorizer {
+    const LOW_COLOR: (u8, u8, u8) = (64, 128, 255);
+    const HIGH_COLOR: (u8, u8, u8) = (255, 64, 64);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn interpolate(from: u8, to: u8, ratio: f64) -> u8 {
+        (f64::from(from) + (f64::from(to) - f64::from(from)) * ratio).round() as u8
+    }
+
+    let numbers: Vec<f64> = col.values.iter().filter_map(ColumnValue::as_f64).collect();
+    let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    Box::new(move |value| match value.as_f64() {
+        Some(n) if max > min => {
+            let ratio = (n - min) / (max - min);
+            style::Color::Rgb(
+                interpolate(LOW_COLOR.0, HIGH_COLOR.0, ratio),
+                interpolate(LOW_COLOR.1, HIGH_COLOR.1, ratio),
+                interpolate(LOW_COLOR.2, HIGH_COLOR.2, ratio),
+            )
+        }
+        _ => colorize_static(value),
+    })
+}
+
+fn colorize_default(_: &ColumnValue) -> style::Color {
+    style::Color::Reset
+}
+
 fn colorize_static(_: &ColumnValue) -> style::Color {
     style::Color::White
 }
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue};
+    use crate::ui::colorizer::{colorize_rgb, select};
+    use crate::ui::{DisplayOptions, Palette};
+    use tui::style;
+
+    #[test]
+    fn select_colors_numeric_column_on_gradient() {
+        let column = Column {
+            name: String::from("latency"),
+            values: (0..20).map(ColumnValue::Integer).chain([ColumnValue::None]).collect(),
+        };
+        let colorize = select(&column, DisplayOptions::DEFAULT);
+        assert_eq!(style::Color::Rgb(64, 128, 255), colorize(&ColumnValue::Integer(0)));
+        assert_eq!(style::Color::Rgb(255, 64, 64), colorize(&ColumnValue::Integer(19)));
+        assert_eq!(style::Color::White, colorize(&ColumnValue::None));
+    }
+
+    #[test]
+    fn select_assigns_separated_hues_to_distinct_values() {
+        const COUNT: usize = 16;
+        let column = Column {
+            name: String::from("category"),
+            values: (0..COUNT).map(|i| ColumnValue::String(format!("value-{i}"))).collect(),
+        };
+        let colorize = select(&column, DisplayOptions::DEFAULT);
+        let colors: Vec<(i32, i32, i32)> = column
+            .values
+            .iter()
+            .map(|v| match colorize(v) {
+                style::Color::Rgb(r, g, b) => (i32::from(r), i32::from(g), i32::from(b)),
+                c => panic!("unexpected color {c:?}"),
+            })
+            .collect();
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                let distance = (a.0 - b.0).abs() + (a.1 - b.1).abs() + (a.2 - b.2).abs();
+                assert!(distance >= 30, "colors {a:?} and {b:?} are too close");
+            }
+        }
+    }
+
+    #[test]
+    fn select_colors_values_apart_up_to_max_colors() {
+        let column = Column {
+            name: String::from("host"),
+            values: (0..20).map(|i| ColumnValue::String(format!("host-{i}"))).collect(),
+        };
+        let distinct_colors = |options: DisplayOptions<'static>| {
+            let colorize = select(&column, options);
+            let mut colors: Vec<String> = column.values.iter().map(|v| format!("{:?}", colorize(v))).collect();
+            colors.sort();
+            colors.dedup();
+            colors.len()
+        };
+        assert_eq!(
+            20,
+            distinct_colors(DisplayOptions {
+                max_colors: 20,
+                ..DisplayOptions::DEFAULT
+            })
+        );
+        // above the limit values still get colors, only from a smaller palette
+        let shared = DisplayOptions {
+            palette: Palette::ColorblindSafe,
+            ..DisplayOptions::DEFAULT
+        };
+        assert!((2..=8).contains(&distinct_colors(shared)));
+    }
+
+    #[test]
+    fn colorize_rgb_gives_the_same_color_in_every_build() {
+        let value = ColumnValue::String(String::from("GET"));
+        assert_eq!(colorize_rgb(&value), colorize_rgb(&ColumnValue::String(String::from("GET"))));
+        // pinned, a different color means that values change colors between builds
+        assert_eq!(style::Color::Rgb(237, 139, 237), colorize_rgb(&value));
+    }
+}
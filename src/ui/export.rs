@@ -0,0 +1,175 @@
+use tui::layout;
+use tui::style;
+
+use crate::io::dataframe::DataFrame;
+use crate::ui::colorizer;
+use crate::ui::table::{format_aligned_cell, get_aligned_decimals, truncate_text};
+use crate::ui::DisplayOptions;
+
+/// Cells of the exported table as shown in the TUI, with the colors of the values.
+struct ExportedTable {
+    header: Vec<String>,
+    rows: Vec<Vec<(String, style::Color)>>,
+}
+
+impl ExportedTable {
+    fn new(df: &dyn DataFrame, options: DisplayOptions<'_>) -> ExportedTable {
+        let names = df.column_names();
+        let mut rows: Vec<Vec<(String, style::Color)>> = vec![Vec::new(); df.len()];
+        let mut header = Vec::new();
+        for name in names {
+            let colorize = colorizer::select(df.column(name), options);
+            let decimals = get_aligned_decimals(df.column(name), options);
+            let texts: Vec<String> = (0..df.len())
+                .map(|i| format_aligned_cell(name, df.get((name, i)), options, decimals))
+                .collect();
+            let width = texts
+                .iter()
+                .map(|text| text.chars().count())
+                .fold(name.chars().count(), usize::max)
+                .min(options.max_string_width.into());
+            let alignment = if df.column(name).is_numeric() {
+                layout::Alignment::Right
+            } else {
+                layout::Alignment::Left
+            };
+            let pad = |text: String| {
+                let text = truncate_text(text, width);
+                if alignment == layout::Alignment::Right {
+                    format!("{text:>width$}")
+                } else {
+                    format!("{text:<width$}")
+                }
+            };
+            for (i, text) in texts.into_iter().enumerate() {
+                rows[i].push((pad(text), colorize(df.get((name, i)))));
+            }
+            header.push(pad(name.clone()));
+        }
+        ExportedTable { header, rows }
+    }
+}
+
+/// Renders the dataframe as a table with ANSI escape codes for the colors of the TUI.
+pub fn to_ansi(df: &dyn DataFrame, options: DisplayOptions<'_>) -> String {
+    let table = ExportedTable::new(df, options);
+    let spacing = " ".repeat(options.column_spacing.into());
+    let header_style = if options.colors { "\x1b[1;33m" } else { "\x1b[1m" };
+    let mut lines = vec![format!("{header_style}{}\x1b[0m", table.header.join(&spacing))];
+    lines.extend(table.rows.into_iter().map(|row| {
+        let cells: Vec<String> = row
+            .into_iter()
+            .map(|(text, color)| match ansi_color(color) {
+                Some(code) => format!("\x1b[{code}m{text}\x1b[0m"),
+                None => text,
+            })
+            .collect();
+        cells.join(&spacing)
+    }));
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Renders the dataframe as an HTML table with inline styles for the colors of the TUI.
+///
+/// Cells keep the padding of the TUI, so the table lines up like the terminal in a monospace font.
+pub fn to_html(df: &dyn DataFrame, options: DisplayOptions<'_>) -> String {
+    let table = ExportedTable::new(df, options);
+    let padding = format!("padding: 0 {}ch; white-space: pre", f32::from(options.column_spacing) / 2.0);
+    let header_color = if options.colors { "; color: #ffff00" } else { "" };
+    let header: Vec<String> = table
+        .header
+        .iter()
+        .map(|name| format!("<th style=\"{padding}{header_color}\">{}</th>", escape_html(name)))
+        .collect();
+    let mut lines = vec![
+        String::from("<table style=\"background-color: #000000; color: #ffffff; font-family: monospace; border-collapse: collapse\">"),
+        format!("<tr>{}</tr>", header.concat()),
+    ];
+    lines.extend(table.rows.into_iter().map(|row| {
+        let cells: Vec<String> = row
+            .into_iter()
+            .map(|(text, color)| {
+                let color = css_color(color).map(|c| format!("; color: {c}")).unwrap_or_default();
+                format!("<td style=\"{padding}{color}\">{}</td>", escape_html(&text))
+            })
+            .collect();
+        format!("<tr>{}</tr>", cells.concat())
+    }));
+    lines.push(String::from("</table>"));
+    let mut html = lines.join("\n");
+    html.push('\n');
+    html
+}
+
+/// Returns the SGR parameters of the foreground color, none for the default color.
+fn ansi_color(color: style::Color) -> Option<String> {
+    match color {
+        style::Color::Rgb(r, g, b) => Some(format!("38;2;{r};{g};{b}")),
+        style::Color::Indexed(i) => Some(format!("38;5;{i}")),
+        style::Color::White => Some(String::from("37")),
+        style::Color::Yellow => Some(String::from("33")),
+        style::Color::Cyan => Some(String::from("36")),
+        _ => None,
+    }
+}
+
+/// Returns the CSS value of the foreground color, none for the default color.
+fn css_color(color: style::Color) -> Option<String> {
+    match color {
+        style::Color::Rgb(r, g, b) => Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        style::Color::White => Some(String::from("#ffffff")),
+        style::Color::Yellow => Some(String::from("#ffff00")),
+        style::Color::Cyan => Some(String::from("#00ffff")),
+        _ => None,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, MaterializedDataFrame};
+    use crate::ui::export::{to_ansi, to_html};
+    use crate::ui::DisplayOptions;
+
+    fn dataframe() -> MaterializedDataFrame {
+        let column = Column {
+            name: String::from("count"),
+            values: (0..20).map(ColumnValue::Integer).collect(),
+        };
+        let records = (0..20).map(|_| serde_json::json!({})).collect();
+        MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records)
+    }
+
+    #[test]
+    fn to_ansi_colors_values_like_the_table() {
+        let ansi = to_ansi(&dataframe(), DisplayOptions::DEFAULT);
+        assert!(ansi.starts_with("\x1b[1;33mcount\x1b[0m\n"), "unexpected header: {ansi:?}");
+        // the lowest and the highest number get the ends of the gradient
+        assert!(ansi.contains("\x1b[38;2;64;128;255m    0\x1b[0m\n"), "unexpected rows: {ansi:?}");
+        assert!(ansi.contains("\x1b[38;2;255;64;64m   19\x1b[0m\n"), "unexpected rows: {ansi:?}");
+
+        let plain = to_ansi(
+            &dataframe(),
+            DisplayOptions {
+                colors: false,
+                ..DisplayOptions::DEFAULT
+            },
+        );
+        assert!(!plain.contains("\x1b[38"), "unexpected colors: {plain:?}");
+    }
+
+    #[test]
+    fn to_html_styles_cells_with_their_colors() {
+        let html = to_html(&dataframe(), DisplayOptions::DEFAULT);
+        assert!(html.contains("color: #4080ff\">    0</td>"), "unexpected cells: {html}");
+        assert!(html.contains("color: #ff4040\">   19</td>"), "unexpected cells: {html}");
+    }
+}
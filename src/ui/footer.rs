@@ -11,14 +11,47 @@ pub struct Footer<'a> {
 }
 
 impl<'a> Footer<'a> {
-    pub fn new<'b>(mode: &'b str, line_number: usize, line_count: usize) -> Footer<'a> {
-        let contents = text::Spans::from(vec![
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<'b>(
+        mode: &'b str,
+        filters: &[&'b str],
+        line_number: usize,
+        line_count: usize,
+        column_range: Option<(usize, usize, usize)>,
+        bookmarks: usize,
+        status: Option<&'b str>,
+        colors: bool,
+    ) -> Footer<'a> {
+        let mut spans = vec![
             text::Span::from("  "),
-            text::Span::styled(format!("[{}]", mode), style::Style::default().add_modifier(style::Modifier::BOLD)),
-            text::Span::from("  "),
-            text::Span::from(format!("{}/{}", line_number, line_count)),
-        ]);
-        let para = widgets::Paragraph::new(contents).style(style::Style::default().add_modifier(style::Modifier::REVERSED));
+            text::Span::styled(format!("[{mode}]"), style::Style::default().add_modifier(style::Modifier::BOLD)),
+        ];
+        // levels are numbered so that they can be selected with the level keys
+        if !filters.is_empty() {
+            let crumbs: Vec<String> = std::iter::once("groups")
+                .chain(filters.iter().copied())
+                .enumerate()
+                .map(|(i, f)| format!("{i}:{f}"))
+                .collect();
+            spans.push(text::Span::from(format!("  {}", crumbs.join(" › "))));
+        }
+        spans.extend([text::Span::from("  "), text::Span::from(format!("{line_number}/{line_count}"))]);
+        if let Some((first, last, count)) = column_range {
+            spans.push(text::Span::from(format!("  cols {first}-{last}/{count}")));
+        }
+        if bookmarks > 0 {
+            spans.push(text::Span::from(format!("  ★ {bookmarks}")));
+        }
+        if let Some(status) = status {
+            spans.push(text::Span::from("  "));
+            spans.push(text::Span::from(status.to_string()));
+        }
+        let contents = text::Spans::from(spans);
+        let para = if colors {
+            widgets::Paragraph::new(contents).style(style::Style::default().add_modifier(style::Modifier::REVERSED))
+        } else {
+            widgets::Paragraph::new(contents)
+        };
 
         Footer { widget: para, height: 1 }
     }
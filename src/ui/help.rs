@@ -0,0 +1,24 @@
+use tui::style;
+use tui::text;
+
+use crate::ui::keys;
+use crate::ui::popup::Popup;
+
+/// Lists the keys of every action and the modes of the table.
+pub fn view<'a>(bindings: &keys::KeyBindings) -> Popup<'a> {
+    let mut lines: Vec<text::Spans> = bindings
+        .iter()
+        .filter(|(_, codes)| !codes.is_empty())
+        .map(|(action, codes)| {
+            let keys: Vec<String> = codes.iter().map(ToString::to_string).collect();
+            text::Spans::from(vec![
+                text::Span::styled(format!("{:<12}", keys.join(", ")), style::Style::default().add_modifier(style::Modifier::BOLD)),
+                text::Span::from(action.description()),
+            ])
+        })
+        .collect();
+    lines.push(text::Spans::default());
+    lines.push(text::Spans::from("GROUPED   rows are distinct values of the group columns"));
+    lines.push(text::Spans::from("FILTERED  rows of the selected group"));
+    Popup::new(String::from(" Help "), lines, 2)
+}
@@ -0,0 +1,30 @@
+use crossterm::event::KeyCode;
+
+use crate::keys::Key;
+pub use crate::keys::{Action, KeyBindings};
+
+/// Returns the key of the code, keys that can't be bound give `None`.
+fn key(code: KeyCode) -> Option<Key> {
+    match code {
+        KeyCode::Char(c) => Some(Key::Char(c)),
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Enter => Some(Key::Enter),
+        KeyCode::Esc => Some(Key::Esc),
+        KeyCode::Tab => Some(Key::Tab),
+        KeyCode::Backspace => Some(Key::Backspace),
+        KeyCode::Home => Some(Key::Home),
+        KeyCode::End => Some(Key::End),
+        KeyCode::PageUp => Some(Key::PageUp),
+        KeyCode::PageDown => Some(Key::PageDown),
+        KeyCode::F(n) => Some(Key::F(n)),
+        _ => None,
+    }
+}
+
+/// Returns the action bound to the key of the code.
+pub fn action(bindings: &KeyBindings, code: KeyCode) -> Option<Action> {
+    key(code).and_then(|key| bindings.action(key))
+}
@@ -0,0 +1,37 @@
+use tui::style;
+use tui::text;
+
+use crate::io::dataframe::{Column, ColumnValue};
+use crate::ui::colorizer;
+use crate::ui::popup::Popup;
+use crate::ui::table::format_cell;
+use crate::ui::DisplayOptions;
+
+/// Number of values listed, the colors of columns with more values don't tell values apart.
+const MAX_ENTRIES: usize = 16;
+
+/// Lists the distinct values of the column in the order of the text shown for them, each with a swatch of its color.
+pub fn view<'a>(column: &Column, options: DisplayOptions<'_>) -> Popup<'a> {
+    let colorize = colorizer::select(column, options);
+    let mut values: Vec<(String, &ColumnValue)> = column
+        .unique()
+        .into_iter()
+        .filter(|v| **v != ColumnValue::None)
+        .map(|v| (format_cell(&column.name, v, options), v))
+        .collect();
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut lines: Vec<text::Spans> = values
+        .iter()
+        .take(MAX_ENTRIES)
+        .map(|(text, value)| {
+            text::Spans::from(vec![
+                text::Span::styled("██ ", style::Style::default().fg(colorize(value))),
+                text::Span::from(text.clone()),
+            ])
+        })
+        .collect();
+    if values.len() > MAX_ENTRIES {
+        lines.push(text::Spans::from(format!("… {} more", values.len() - MAX_ENTRIES)));
+    }
+    Popup::new(format!(" {} ", column.name), lines, 0)
+}
@@ -1,25 +1,133 @@
 mod app;
+mod bar;
 mod card;
 mod colorizer;
+mod export;
 mod footer;
+mod help;
+mod keys;
+mod legend;
+mod picker;
+mod popup;
 mod table;
 mod timeline;
+mod value;
 
+use std::collections::HashSet;
+use std::error::Error;
 use std::io;
+use std::sync::mpsc;
+use std::time::Duration;
 
-use crossterm::{event, execute, terminal};
+use crossterm::{cursor, event, execute, terminal};
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
+use crate::configuration::InputSpec;
 use crate::io::dataframe;
+use crate::io::format::ColumnFormat;
+use crate::keys::KeyBindings;
 
+pub use colorizer::Palette;
+pub use export::{to_ansi, to_html};
+pub use table::HighlightStyle;
+
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct DisplayOptions<'a> {
+    /// Maximum width of a table column in characters, longer values are truncated
+    pub max_string_width: u16,
+    /// Whether values and UI elements are colored
+    pub colors: bool,
+    /// Palette used for columns with few distinct values
+    pub palette: Palette,
+    /// Most distinct values that get colors of their own, more values share colors
+    pub max_colors: usize,
+    /// Whether a leading column shows 1-based row numbers
+    pub line_numbers: bool,
+    /// Whether every other row has a shaded background
+    pub zebra: bool,
+    /// Whether a row of column totals is pinned below the table
+    pub totals: bool,
+    /// Number of spaces between table columns
+    pub column_spacing: u16,
+    /// Whether the table is drawn inside a border
+    pub borders: bool,
+    /// Placeholder shown in place of missing values
+    pub null_value: &'a str,
+    /// Text shown for true values
+    pub true_value: &'a str,
+    /// Text shown for false values
+    pub false_value: &'a str,
+    /// Column whose values order the groups, after their order when grouped
+    pub sort_groups: Option<&'a dataframe::ColumnOrder>,
+    /// Columns shown first in the given order, other columns follow in their own order
+    pub column_order: &'a [String],
+    /// Columns left out of the table, they are still used for grouping and filtering
+    pub hidden_columns: &'a [String],
+    /// Separator of thousands in numbers, numbers are shown without separators if not set
+    pub thousands_separator: Option<&'a str>,
+    /// Number of decimal places of floats, floats are shown with all their digits if not set
+    pub decimals: Option<usize>,
+    /// Whether moving up from the first row selects the last row and moving down from the last row selects the first
+    pub wrap_around: bool,
+    /// Style of the selected row
+    pub highlight: table::HighlightStyle,
+    /// Whether decimal points of floats line up, unless the number of decimal places is set
+    pub align_decimals: bool,
+    /// Display formats of columns
+    pub column_formats: &'a [(String, ColumnFormat)],
+}
+
+#[cfg(test)]
+impl DisplayOptions<'static> {
+    /// Options of tests, which change the fields they are about.
+    pub const DEFAULT: DisplayOptions<'static> = DisplayOptions {
+        max_string_width: 32,
+        colors: true,
+        palette: Palette::Rgb,
+        max_colors: 16,
+        line_numbers: false,
+        zebra: false,
+        column_spacing: 2,
+        borders: false,
+        totals: false,
+        null_value: "∅",
+        true_value: "+",
+        false_value: "-",
+        sort_groups: None,
+        column_order: &[],
+        hidden_columns: &[],
+        thousands_separator: None,
+        decimals: None,
+        highlight: HighlightStyle::Reversed,
+        wrap_around: true,
+        align_decimals: false,
+        column_formats: &[],
+    };
+}
+
+/// Source of updated data in watch mode.
+pub struct Reloader<'a> {
+    /// Receives a message whenever the input changes
+    pub changes: mpsc::Receiver<()>,
+    pub load: Box<dyn Fn() -> Result<dataframe::MaterializedDataFrame, Box<dyn Error>> + 'a>,
+}
+
+/// Shows the dataframe until the user quits, returns the raw record picked to be printed on exit.
 pub fn show_dataframe(
-    df: &dataframe::MaterializedDataFrame,
-    group_columns: &[String],
-    show_in_grouped_mode: &[String],
-    timeline_column: &Option<String>,
-) -> Result<(), io::Error> {
+    df: dataframe::MaterializedDataFrame,
+    reloader: Option<&Reloader>,
+    spec: &InputSpec,
+    grouping: &dataframe::GroupOptions<'_>,
+    options: DisplayOptions<'_>,
+    confirm_quit: bool,
+) -> Result<Option<String>, Box<dyn Error>> {
+    const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+    let bindings = &spec.keys;
+
     // prepare tui
+    install_panic_hook();
     let mut stdout = io::stdout();
     execute!(stdout, event::EnableMouseCapture, terminal::EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
@@ -28,41 +136,279 @@ pub fn show_dataframe(
     term.clear()?;
 
     // draw table
-    let mut app_view_model = app::ViewModel::new(df, group_columns, show_in_grouped_mode, timeline_column);
-    loop {
-        term.draw(|f| app::View::new(&mut app_view_model).render(f))?;
-        match event::read()? {
-            event::Event::Key(key) => {
-                if key.code == event::KeyCode::Char('c') && key.modifiers == event::KeyModifiers::CONTROL {
-                    break;
+    let mut df = df;
+    let mut drill_path = Vec::new();
+    let mut bookmarks = HashSet::new();
+    let mut show_timeline = true;
+    let mut group_by = spec.group_by.clone();
+    let mut session = Session {
+        confirm_quit,
+        ..Session::default()
+    };
+    'reload: loop {
+        // a column picked to group by is no longer an extra column
+        let show_in_grouped: Vec<String> = spec.show_in_grouped.iter().filter(|c| !group_by.contains(c)).cloned().collect();
+        let mut app_view_model = app::ViewModel::new(&df, &group_by, &show_in_grouped, spec, grouping, options);
+        app_view_model.bookmarks = std::mem::take(&mut bookmarks);
+        app_view_model.restore_drill_path(&drill_path);
+        if !show_timeline {
+            app_view_model.toggle_timeline();
+        }
+        let updated = loop {
+            term.draw(|f| app::View::new(&mut app_view_model).render(f))?;
+            if let Some(reloader) = reloader {
+                if reloader.changes.try_iter().count() > 0 {
+                    // keep showing the previous data if the input is not readable yet
+                    match (reloader.load)() {
+                        Ok(updated) => break Some(updated),
+                        Err(e) => app_view_model.status = Some(format!("failed to reload input: {e}")),
+                    }
                 }
-                match key.code {
-                    event::KeyCode::Char('w') | event::KeyCode::Up => app_view_model.move_selected(true),
-                    event::KeyCode::Char('s') | event::KeyCode::Down => app_view_model.move_selected(false),
-                    event::KeyCode::Enter => app_view_model.focus(),
-                    event::KeyCode::Char('q') | event::KeyCode::Esc => {
-                        if !app_view_model.back() {
-                            break;
-                        }
+                if !event::poll(WATCH_POLL_INTERVAL)? {
+                    continue;
+                }
+            }
+
+            match event::read()? {
+                event::Event::Key(key) => {
+                    if key.code == event::KeyCode::Char('c') && key.modifiers == event::KeyModifiers::CONTROL {
+                        break 'reload;
+                    }
+                    if !handle_key(key.code, &mut app_view_model, bindings, &mut session) {
+                        break 'reload;
+                    }
+                    if session.regroup.is_some() {
+                        break None;
                     }
-                    _ => {}
                 }
+
+                event::Event::Mouse(me) => match me.kind {
+                    event::MouseEventKind::ScrollDown => app_view_model.move_selected(false),
+                    event::MouseEventKind::ScrollUp => app_view_model.move_selected(true),
+                    event::MouseEventKind::Down(event::MouseButton::Left) if app_view_model.overlay.is_none() => {
+                        app_view_model.click(me.column, me.row);
+                    }
+                    _ => {}
+                },
+                event::Event::Resize(_, _) => {}
             }
+        };
 
-            event::Event::Mouse(me) => match me.kind {
-                event::MouseEventKind::ScrollDown => app_view_model.move_selected(false),
-                event::MouseEventKind::ScrollUp => app_view_model.move_selected(true),
-                _ => {}
-            },
-            event::Event::Resize(_, _) => {}
+        drill_path = app_view_model.drill_path();
+        bookmarks = std::mem::take(&mut app_view_model.bookmarks);
+        show_timeline = app_view_model.timeline_shown();
+        drop(app_view_model);
+        // bookmarks are row indexes, which refer to other records once the input is reloaded
+        if let Some(updated) = updated {
+            df = updated;
+            bookmarks.clear();
+        }
+        // the drill path leads through groups of the previous group columns
+        if let Some(columns) = session.regroup.take() {
+            group_by = columns;
+            drill_path.clear();
+            df.reset_group_index();
         }
     }
 
     // clean up tui
     term.clear()?;
+    restore_terminal()?;
+
+    Ok(session.picked)
+}
+
+/// Restores the terminal before the panic message is printed so that a panic doesn't leave it in raw mode.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal().ok();
+        default_hook(info);
+    }));
+}
+
+fn restore_terminal() -> io::Result<()> {
     terminal::disable_raw_mode()?;
-    execute!(term.backend_mut(), terminal::LeaveAlternateScreen, event::DisableMouseCapture)?;
-    term.show_cursor()?;
+    execute!(io::stdout(), terminal::LeaveAlternateScreen, event::DisableMouseCapture, cursor::Show)
+}
+
+/// State kept while the dataframe is shown, including reloads.
+#[derive(Default)]
+struct Session {
+    clipboard: Option<arboard::Clipboard>,
+    /// Digits typed after the jump key
+    jump_input: Option<String>,
+    /// Text typed after the search key
+    search_input: Option<String>,
+    /// Raw record to print on exit
+    picked: Option<String>,
+    /// Whether quitting has to be confirmed
+    confirm_quit: bool,
+    /// Group columns chosen in the column picker, applied by rebuilding the view
+    regroup: Option<Vec<String>>,
+}
+
+/// Runs the action bound to the key, returns false when the user quits.
+fn handle_key(code: event::KeyCode, app_view_model: &mut app::ViewModel, bindings: &KeyBindings, session: &mut Session) -> bool {
+    app_view_model.status = None;
+    match app_view_model.overlay {
+        Some(app::Overlay::ConfirmQuit) => {
+            app_view_model.overlay = None;
+            return code != event::KeyCode::Char('y');
+        }
+        Some(app::Overlay::Picker(_)) => {
+            handle_picker_input(code, app_view_model, bindings, session);
+            return true;
+        }
+        // any key closes the other overlays
+        Some(_) => {
+            app_view_model.overlay = None;
+            return true;
+        }
+        None => {}
+    }
+    if session.jump_input.is_some() {
+        handle_jump_input(&mut session.jump_input, code, app_view_model);
+        return true;
+    }
+    if session.search_input.is_some() {
+        handle_search_input(&mut session.search_input, code, app_view_model);
+        return true;
+    }
+    match keys::action(bindings, code) {
+        Some(keys::Action::MoveUp) => app_view_model.move_selected(true),
+        Some(keys::Action::MoveDown) => app_view_model.move_selected(false),
+        Some(keys::Action::PageUp) => app_view_model.page(true),
+        Some(keys::Action::PageDown) => app_view_model.page(false),
+        Some(keys::Action::First) => app_view_model.jump_to(0),
+        Some(keys::Action::Last) => app_view_model.jump_to(usize::MAX),
+        Some(keys::Action::ColumnLeft) => app_view_model.move_selected_column(true),
+        Some(keys::Action::ColumnRight) => app_view_model.move_selected_column(false),
+        Some(keys::Action::Expand) => app_view_model.overlay = Some(app::Overlay::Value),
+        Some(keys::Action::Legend) => app_view_model.show_legend(),
+        Some(keys::Action::Jump) => {
+            session.jump_input = Some(String::new());
+            app_view_model.status = Some(String::from("go to row: "));
+        }
+        Some(keys::Action::Search) => {
+            session.search_input = Some(String::new());
+            app_view_model.status = Some(String::from("search: "));
+        }
+        Some(keys::Action::Focus) => app_view_model.focus(),
+        Some(keys::Action::Exclude) => app_view_model.exclude(),
+        Some(keys::Action::Bookmark) => app_view_model.toggle_bookmark(),
+        Some(keys::Action::ShowBookmarks) => app_view_model.show_bookmarks(),
+        Some(keys::Action::Timeline) => app_view_model.toggle_timeline(),
+        Some(keys::Action::Regroup) => app_view_model.open_column_picker(),
+        Some(keys::Action::Level) => {
+            if let Some(level) = key_digit(code) {
+                app_view_model.back_to_level(level);
+            }
+        }
+        Some(keys::Action::Back) if app_view_model.back() => {}
+        Some(keys::Action::Back | keys::Action::Quit) => {
+            if !session.confirm_quit {
+                return false;
+            }
+            app_view_model.overlay = Some(app::Overlay::ConfirmQuit);
+            app_view_model.status = Some(String::from("Quit? (y/n)"));
+        }
+        Some(keys::Action::Copy) => {
+            if let Some(raw) = app_view_model.focused_raw().cloned() {
+                app_view_model.status = Some(match copy_to_clipboard(&mut session.clipboard, &raw) {
+                    Ok(()) => String::from("copied record to clipboard"),
+                    Err(e) => format!("failed to copy record: {e}"),
+                });
+            }
+        }
+        Some(keys::Action::Pick) => {
+            if let Some(raw) = app_view_model.focused_raw() {
+                session.picked = Some(raw.clone());
+                app_view_model.status = Some(String::from("record will be printed on exit"));
+            }
+        }
+        Some(keys::Action::Help) => app_view_model.overlay = Some(app::Overlay::Help),
+        None => {}
+    }
+    true
+}
+
+fn key_digit(code: event::KeyCode) -> Option<usize> {
+    match code {
+        event::KeyCode::Char(c) => c.to_digit(10).map(|d| d as usize),
+        _ => None,
+    }
+}
+
+/// Accumulates digits of a row number, Enter selects the row and other keys cancel the input.
+fn handle_jump_input(jump_input: &mut Option<String>, code: event::KeyCode, app_view_model: &mut app::ViewModel) {
+    let Some(input) = jump_input else {
+        return;
+    };
+    match code {
+        event::KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+        event::KeyCode::Backspace => {
+            input.pop();
+        }
+        event::KeyCode::Enter if !input.is_empty() => {
+            // only digits are accepted, so parsing fails on overflow only
+            let line_number = input.parse::<usize>().unwrap_or(usize::MAX);
+            app_view_model.jump_to(line_number.saturating_sub(1));
+            *jump_input = None;
+        }
+        _ => *jump_input = None,
+    }
+    app_view_model.status = jump_input.as_ref().map(|input| format!("go to row: {input}"));
+}
+
+/// Accumulates the search text, Enter searches for it and Esc cancels the input.
+fn handle_search_input(search_input: &mut Option<String>, code: event::KeyCode, app_view_model: &mut app::ViewModel) {
+    let Some(input) = search_input else {
+        return;
+    };
+    match code {
+        event::KeyCode::Char(c) => input.push(c),
+        event::KeyCode::Backspace => {
+            input.pop();
+        }
+        event::KeyCode::Enter => {
+            let query = std::mem::take(input);
+            *search_input = None;
+            app_view_model.status = None;
+            app_view_model.search(&query);
+            return;
+        }
+        _ => *search_input = None,
+    }
+    app_view_model.status = search_input.as_ref().map(|input| format!("search: {input}"));
+}
+
+/// Moves through the column picker, Space checks a column, Enter regroups by the checked columns and Esc closes it.
+fn handle_picker_input(code: event::KeyCode, app_view_model: &mut app::ViewModel, bindings: &KeyBindings, session: &mut Session) {
+    let Some(app::Overlay::Picker(picker)) = &mut app_view_model.overlay else {
+        return;
+    };
+    match (code, keys::action(bindings, code)) {
+        (event::KeyCode::Char(' '), _) => picker.toggle(),
+        (event::KeyCode::Enter, _) => match picker.chosen() {
+            Some(columns) => {
+                session.regroup = Some(columns);
+                app_view_model.overlay = None;
+            }
+            None => app_view_model.status = Some(String::from("check at least one column to group by")),
+        },
+        (event::KeyCode::Esc, _) => app_view_model.overlay = None,
+        (_, Some(keys::Action::MoveUp)) => picker.move_selected(true),
+        (_, Some(keys::Action::MoveDown)) => picker.move_selected(false),
+        _ => {}
+    }
+}
 
-    Ok(())
+/// Copies text to the system clipboard, the clipboard is kept open because some platforms drop its contents on close.
+fn copy_to_clipboard(clipboard: &mut Option<arboard::Clipboard>, text: &str) -> Result<(), arboard::Error> {
+    let clipboard = match clipboard {
+        Some(clipboard) => clipboard,
+        None => clipboard.insert(arboard::Clipboard::new()?),
+    };
+    clipboard.set_text(text)
 }
@@ -0,0 +1,91 @@
+use tui::style;
+use tui::text;
+
+use crate::ui::popup::Popup;
+
+/// Columns with checkboxes, the checked columns become the group columns.
+pub struct ColumnPicker {
+    columns: Vec<String>,
+    checked: Vec<bool>,
+    selected: usize,
+}
+
+impl ColumnPicker {
+    /// Lists the columns with the group columns checked, in the order they are grouped by first.
+    pub fn new(columns: &[&String], group_columns: &[String]) -> ColumnPicker {
+        let mut columns: Vec<String> = columns.iter().map(|c| (*c).clone()).collect();
+        columns.sort_by_key(|c| group_columns.iter().position(|g| g == c).unwrap_or(usize::MAX));
+        let checked = columns.iter().map(|c| group_columns.contains(c)).collect();
+        ColumnPicker {
+            columns,
+            checked,
+            selected: 0,
+        }
+    }
+
+    pub fn move_selected(&mut self, up: bool) {
+        self.selected = if up {
+            self.selected.saturating_sub(1)
+        } else {
+            (self.selected + 1).min(self.columns.len().saturating_sub(1))
+        };
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(checked) = self.checked.get_mut(self.selected) {
+            *checked = !*checked;
+        }
+    }
+
+    /// Returns the checked columns, or None if no column is checked.
+    pub fn chosen(&self) -> Option<Vec<String>> {
+        let chosen: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&self.checked)
+            .filter(|(_, checked)| **checked)
+            .map(|(c, _)| c.clone())
+            .collect();
+        (!chosen.is_empty()).then_some(chosen)
+    }
+}
+
+/// Lists the columns with their checkboxes, scrolled to the selected column.
+pub fn view<'a>(picker: &ColumnPicker) -> Popup<'a> {
+    let lines: Vec<text::Spans> = picker
+        .columns
+        .iter()
+        .zip(&picker.checked)
+        .enumerate()
+        .map(|(i, (column, checked))| {
+            let text = format!("[{}] {column}", if *checked { 'x' } else { ' ' });
+            let style = if i == picker.selected {
+                style::Style::default().add_modifier(style::Modifier::REVERSED)
+            } else {
+                style::Style::default()
+            };
+            text::Spans::from(text::Span::styled(text, style))
+        })
+        .collect();
+    Popup::new(String::from(" group by (Space toggles, Enter regroups) "), lines, 0).select(picker.selected)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::picker::ColumnPicker;
+
+    #[test]
+    fn chosen_columns_follow_checkboxes() {
+        let names = [String::from("host"), String::from("status"), String::from("path")];
+        let columns: Vec<&String> = names.iter().collect();
+        let mut picker = ColumnPicker::new(&columns, &[String::from("status")]);
+        assert_eq!(Some(vec![String::from("status")]), picker.chosen());
+
+        picker.toggle();
+        assert_eq!(None, picker.chosen());
+        picker.move_selected(false);
+        picker.move_selected(false);
+        picker.toggle();
+        assert_eq!(Some(vec![String::from("path")]), picker.chosen());
+    }
+}
@@ -0,0 +1,82 @@
+use tui::backend;
+use tui::layout;
+use tui::text;
+use tui::widgets;
+use tui::Frame;
+
+/// Lines in a bordered box centred over the table, as large as the lines if the area allows.
+pub struct Popup<'a> {
+    title: String,
+    lines: Vec<text::Spans<'a>>,
+    width: u16,
+    height: u16,
+    /// Line scrolled into view if the box is cut to the height of the area
+    selected: usize,
+}
+
+impl<'a> Popup<'a> {
+    /// Boxes the lines under the title, `padding` blank columns are added beside the lines.
+    pub fn new(title: String, lines: Vec<text::Spans<'a>>, padding: usize) -> Popup<'a> {
+        let width = lines.iter().map(text::Spans::width).fold(title.chars().count(), usize::max) + padding;
+        let height = lines.len();
+        #[allow(clippy::cast_possible_truncation)]
+        Popup {
+            title,
+            lines,
+            width: (width + 2).min(u16::MAX.into()) as u16,
+            height: (height + 2).min(u16::MAX.into()) as u16,
+            selected: 0,
+        }
+    }
+
+    /// Keeps the line visible by scrolling the lines if they don't fit.
+    pub fn select(mut self, line: usize) -> Popup<'a> {
+        self.selected = line;
+        self
+    }
+
+    pub fn render<B: backend::Backend>(self, f: &mut Frame<B>, size: layout::Rect) {
+        let area = centered(size, self.width, self.height);
+        let offset = scroll_offset(self.selected, area.height.saturating_sub(2));
+        let para = widgets::Paragraph::new(self.lines)
+            .block(widgets::Block::default().title(self.title).borders(widgets::Borders::ALL))
+            .scroll((offset, 0));
+        f.render_widget(widgets::Clear, area);
+        f.render_widget(para, area);
+    }
+}
+
+/// Returns the area of the width and height in the middle of the area, cut to fit it.
+pub fn centered(size: layout::Rect, width: u16, height: u16) -> layout::Rect {
+    let width = width.min(size.width);
+    let height = height.min(size.height);
+    layout::Rect::new(size.x + (size.width - width) / 2, size.y + (size.height - height) / 2, width, height)
+}
+
+/// Number of lines scrolled past so that the line is the last of the visible lines once it is beyond the first of them.
+fn scroll_offset(line: usize, visible: u16) -> u16 {
+    let offset = (line + 1).saturating_sub(usize::from(visible).max(1));
+    u16::try_from(offset).unwrap_or(u16::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::popup::{centered, scroll_offset};
+    use tui::layout;
+
+    #[test]
+    fn selected_line_stays_visible() {
+        assert_eq!(0, scroll_offset(0, 3));
+        assert_eq!(0, scroll_offset(2, 3));
+        assert_eq!(1, scroll_offset(3, 3));
+        assert_eq!(7, scroll_offset(9, 3));
+        assert_eq!(9, scroll_offset(9, 0));
+    }
+
+    #[test]
+    fn centered_area_fits_in_area() {
+        let size = layout::Rect::new(0, 1, 20, 10);
+        assert_eq!(layout::Rect::new(5, 3, 10, 6), centered(size, 10, 6));
+        assert_eq!(layout::Rect::new(0, 1, 20, 10), centered(size, 30, 12));
+    }
+}
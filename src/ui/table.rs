@@ -1,27 +1,112 @@
+use std::collections::{HashMap, HashSet};
+
 use tui::backend;
 use tui::layout;
 use tui::style;
+use tui::text;
 use tui::widgets;
 use tui::Frame;
 
 use crate::io::dataframe;
 use crate::io::dataframe::DataFrame;
 use crate::ui::colorizer;
+use crate::ui::DisplayOptions;
+
+/// Style of the selected row, on top of the colors of its values.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum HighlightStyle {
+    /// Foreground and background are swapped
+    #[default]
+    Reversed,
+    Bold,
+    Underlined,
+}
+
+impl HighlightStyle {
+    fn style(self) -> style::Style {
+        let modifier = match self {
+            HighlightStyle::Reversed => style::Modifier::REVERSED,
+            HighlightStyle::Bold => style::Modifier::BOLD,
+            HighlightStyle::Underlined => style::Modifier::UNDERLINED,
+        };
+        style::Style::default().add_modifier(modifier)
+    }
+}
+
+impl std::str::FromStr for HighlightStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reversed" => Ok(HighlightStyle::Reversed),
+            "bold" => Ok(HighlightStyle::Bold),
+            "underlined" => Ok(HighlightStyle::Underlined),
+            _ => Err(format!("unknown highlight style {s}")),
+        }
+    }
+}
 
 pub struct ViewModel<'a> {
     pub df: Box<dyn DataFrame + 'a>,
     pub selected: usize,
-    timeline_column: Option<Vec<String>>,
+    /// Columns that stay on the left while the other columns scroll horizontally
+    frozen_columns: &'a [String],
+    /// Number of scrolled columns hidden on the left
+    col_offset: usize,
+    /// Index of the selected column among the frozen and then the scrolled columns
+    selected_column: usize,
+    charts: Vec<ChartColumn>,
+    /// Whether the timeline chart is shown
+    pub show_timeline: bool,
     table_state: widgets::TableState,
+    options: DisplayOptions<'a>,
+    /// Number of rows visible during the last render
+    page_height: usize,
+    /// Area and index of the first visible row during the last render
+    area: layout::Rect,
+    offset: usize,
+    /// Text highlighted in the cells, matched ignoring case
+    search: Option<String>,
+    /// Decimal places of the floats of every column and the width of its widest cell, the rows don't change once shown
+    column_layouts: HashMap<String, (Option<usize>, usize)>,
 }
 
 impl<'a> ViewModel<'a> {
-    pub fn new(df: Box<dyn DataFrame + 'a>, timeline_column: Option<Vec<String>>) -> ViewModel<'a> {
+    pub fn new(
+        df: Box<dyn DataFrame + 'a>,
+        frozen_columns: &'a [String],
+        charts: Vec<ChartColumn>,
+        options: DisplayOptions<'a>,
+    ) -> ViewModel<'a> {
+        let column_layouts = df
+            .column_names()
+            .into_iter()
+            .map(|name| {
+                let column = df.column(name);
+                let decimals = get_aligned_decimals(column, options);
+                let width = column
+                    .values
+                    .iter()
+                    .map(|v| format_aligned_cell(name, v, options, decimals).chars().count())
+                    .fold(name.chars().count(), usize::max);
+                (name.clone(), (decimals, width))
+            })
+            .collect();
         let mut model = ViewModel {
             df,
-            timeline_column,
+            frozen_columns,
+            col_offset: 0,
+            selected_column: 0,
+            charts,
+            show_timeline: true,
             table_state: widgets::TableState::default(),
             selected: 0,
+            options,
+            page_height: 1,
+            area: layout::Rect::default(),
+            offset: 0,
+            search: None,
+            column_layouts,
         };
         model.set_selected(0);
         model
@@ -32,109 +117,1029 @@ impl<'a> ViewModel<'a> {
         self.table_state.select(Some(value));
     }
 
+    /// Selects the previous or the next row, wrapping around at the first and the last row unless `wrap_around` is off.
     pub fn move_selected(&mut self, up: bool) {
-        let new_index = {
-            let len = self.df.len();
+        let len = self.df.len();
+        if len == 0 {
+            return;
+        }
+        let new_index = if self.options.wrap_around {
             (self.selected + (if up { len - 1 } else { 1 })) % len
+        } else if up {
+            self.selected.saturating_sub(1)
+        } else {
+            (self.selected + 1).min(len - 1)
         };
         self.set_selected(new_index);
     }
 
-    pub fn selected_row(&self) -> Vec<&dataframe::ColumnValue> {
-        self.df.column_names().iter().map(|c| self.df.get((c, self.selected))).collect()
+    /// Selects the row at the index, clamped to the last row.
+    pub fn jump_to(&mut self, index: usize) {
+        self.set_selected(index.min(self.df.len().saturating_sub(1)));
+    }
+
+    /// Moves the selection by the number of visible rows without wrapping around.
+    pub fn page(&mut self, up: bool) {
+        let index = if up {
+            self.selected.saturating_sub(self.page_height)
+        } else {
+            self.selected.saturating_add(self.page_height)
+        };
+        self.jump_to(index);
+    }
+
+    /// Selects the next column, scrolling the columns that are not frozen to keep it visible.
+    pub fn move_selected_column(&mut self, left: bool) {
+        let frozen = self.get_frozen_column_names().len();
+        if left {
+            self.selected_column = self.selected_column.saturating_sub(1);
+            if self.selected_column >= frozen {
+                self.col_offset = self.col_offset.min(self.selected_column - frozen);
+            }
+        } else {
+            self.selected_column = (self.selected_column + 1).min(self.get_ordered_column_names().len().saturating_sub(1));
+            while self.selected_column >= frozen
+                && self.col_offset < self.selected_column - frozen
+                && self
+                    .get_column_range(self.area.width)
+                    .is_some_and(|(_, last, _)| self.selected_column >= last)
+            {
+                self.col_offset += 1;
+            }
+        }
+    }
+
+    /// Returns the name of the selected column.
+    pub fn get_selected_column_name(&self) -> &String {
+        let mut names = self.get_frozen_column_names();
+        names.extend(self.get_scrolled_column_names());
+        names[self.selected_column]
+    }
+
+    /// Returns the position of the selected column among the visible columns.
+    fn get_selected_visible_column(&self) -> Option<usize> {
+        let frozen = self.get_frozen_column_names().len();
+        if self.selected_column < frozen {
+            Some(self.selected_column)
+        } else {
+            self.selected_column.checked_sub(self.col_offset).filter(|i| *i >= frozen)
+        }
+    }
+
+    /// Returns the 1-based range of the columns shown in the width and the column count if any column is hidden.
+    pub fn get_column_range(&self, width: u16) -> Option<(usize, usize, usize)> {
+        let count = self.get_ordered_column_names().len();
+        let frozen = self.get_frozen_column_names().len();
+        let fitting = self.get_shown_column_widths(width).len();
+        if self.col_offset == 0 && fitting == count {
+            return None;
+        }
+
+        // scrolled columns are not shown at all if the frozen columns don't fit
+        if fitting <= frozen {
+            return Some((1, fitting.max(1), count));
+        }
+        let first = frozen + self.col_offset + 1;
+        Some((first, self.col_offset + fitting, count))
+    }
+
+    pub fn has_timeline(&self) -> bool {
+        self.charts.iter().any(|chart| chart.timeline)
+    }
+
+    fn get_shown_charts(&self) -> impl Iterator<Item = &ChartColumn> {
+        self.charts.iter().filter(|chart| self.show_timeline || !chart.timeline)
+    }
+
+    /// Returns the width taken by the shown charts after the data columns.
+    fn get_charts_width(&self) -> usize {
+        let spacing = usize::from(self.options.column_spacing);
+        self.get_shown_charts().map(|chart| usize::from(chart.width) + spacing).sum()
+    }
+
+    /// Returns widths of the visible columns that fit in the width, the columns that don't fit are left out.
+    ///
+    /// The first column is narrowed to the width if even it doesn't fit so that the table is never empty.
+    fn get_shown_column_widths(&self, width: u16) -> Vec<usize> {
+        let spacing = usize::from(self.options.column_spacing);
+        let available = usize::from(width)
+            .saturating_sub(HIGHLIGHT_SYMBOL.len() + self.get_line_number_width().map_or(0, |w| w + spacing) + self.get_charts_width());
+        let mut widths = self.get_column_widths();
+        let mut remaining = available;
+        let fitting = widths
+            .iter()
+            .take_while(|w| {
+                let fits = **w <= remaining;
+                remaining = remaining.saturating_sub(**w + spacing);
+                fits
+            })
+            .count();
+        widths.truncate(fitting.max(1));
+        if fitting == 0 {
+            if let Some(first) = widths.first_mut() {
+                *first = (*first).min(available);
+            }
+        }
+        widths
+    }
+
+    fn get_column_widths(&self) -> Vec<usize> {
+        self.get_visible_column_names()
+            .into_iter()
+            .map(|name| self.column_layouts[name].1.min(self.options.max_string_width.into()))
+            .collect()
+    }
+
+    /// Returns the width of the row number column if it is shown.
+    fn get_line_number_width(&self) -> Option<usize> {
+        self.options.line_numbers.then(|| self.df.len().to_string().len())
+    }
+
+    /// Returns the frozen columns followed by the scrolled columns.
+    fn get_visible_column_names(&self) -> Vec<&String> {
+        let mut names = self.get_frozen_column_names();
+        names.extend(self.get_scrolled_column_names().into_iter().skip(self.col_offset));
+        names
+    }
+
+    fn get_scrolled_column_names(&self) -> Vec<&String> {
+        self.get_ordered_column_names()
+            .into_iter()
+            .filter(|name| !self.frozen_columns.contains(name))
+            .collect()
+    }
+
+    fn get_frozen_column_names(&self) -> Vec<&String> {
+        self.get_ordered_column_names()
+            .into_iter()
+            .filter(|name| self.frozen_columns.contains(name))
+            .collect()
+    }
+
+    /// Returns names of columns that are not hidden, columns of the display order first and other columns in their order.
+    fn get_ordered_column_names(&self) -> Vec<&String> {
+        let mut names = self.df.column_names();
+        names.retain(|name| !self.options.hidden_columns.contains(name));
+        names.sort_by_key(|name| {
+            self.options
+                .column_order
+                .iter()
+                .position(|c| c == *name)
+                .unwrap_or(self.options.column_order.len())
+        });
+        names
+    }
+
+    /// Highlights the text and selects the next row containing it, returns false if no row contains it.
+    pub fn search(&mut self, query: &str) -> bool {
+        if query.is_empty() {
+            self.search = None;
+            return true;
+        }
+        self.search = Some(String::from(query));
+        let query = query.to_lowercase();
+        let len = self.df.len();
+        let names = self.get_ordered_column_names();
+        let found = (1..=len).map(|step| (self.selected + step) % len).find(|i| {
+            names.iter().any(|name| {
+                format_cell(name, self.df.get((name, *i)), self.options)
+                    .to_lowercase()
+                    .contains(&query)
+            })
+        });
+        if let Some(index) = found {
+            self.set_selected(index);
+        }
+        found.is_some()
     }
+
+    /// Returns the index of the row displayed at the screen position, if any.
+    pub fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let rows_top = self.area.y + TABLE_HEADER_HEIGHT;
+        if column < self.area.x || column >= self.area.right() || row < rows_top || row >= self.area.bottom() {
+            return None;
+        }
+        let index = self.offset + usize::from(row - rows_top);
+        (index < self.df.len()).then_some(index)
+    }
+
+    /// Scrolls the visible rows the same way the table widget does to keep the selection visible.
+    fn update_offset(&mut self) {
+        let offset = self.offset.min(self.df.len().saturating_sub(1));
+        self.offset = if self.selected >= offset + self.page_height {
+            self.selected + 1 - self.page_height
+        } else {
+            offset.min(self.selected)
+        };
+    }
+}
+
+/// Column drawn after the data columns with a prerendered cell for every row.
+pub struct ChartColumn {
+    pub cells: Vec<String>,
+    pub width: u16,
+    /// Whether the column is the timeline, which can be hidden
+    pub timeline: bool,
 }
 
 pub struct View<'a: 'b, 'b> {
     view_model: &'b mut ViewModel<'a>,
 }
 
-pub const MAX_STRING_WIDTH: u16 = 32;
 pub const TIMELINE_WIDTH: u16 = 32;
 
+const HIGHLIGHT_SYMBOL: &str = "> ";
+const TOTALS_SYMBOL: &str = "Σ ";
+const NEWLINE_SYMBOL: char = '⏎';
+
+const STRIPE_COLOR: style::Color = style::Color::Rgb(40, 40, 48);
+
+/// Rows taken by the header and its bottom margin
+const TABLE_HEADER_HEIGHT: u16 = 2;
+
 impl<'a: 'c, 'c> View<'a, 'c> {
     pub fn new(view_model: &'c mut ViewModel<'a>) -> View<'a, 'c> {
         View { view_model }
     }
 
     pub fn render<B: backend::Backend>(self, f: &mut Frame<B>, size: layout::Rect) {
+        let size = if self.view_model.options.borders {
+            let block = widgets::Block::default().borders(widgets::Borders::ALL);
+            let inner = block.inner(size);
+            f.render_widget(block, size);
+            inner
+        } else {
+            size
+        };
+
         // create table widget
-        let column_widths = self.get_column_widths();
-        let table_contents = self.get_table_contents();
+        let column_widths = self.view_model.get_shown_column_widths(size.width);
+        let hidden_columns = self.view_model.get_visible_column_names().len() - column_widths.len();
+        let column_alignments = self.get_column_alignments(&column_widths);
+        let column_constraints = self.get_column_constraints(&column_widths);
+        let table_contents = self.get_table_contents(&column_widths, &column_alignments);
+        let mut highlight_style = self.view_model.options.highlight.style();
+        if self.is_striped() {
+            // the selected row is never shaded
+            highlight_style = highlight_style.bg(style::Color::Reset);
+        }
         let table_widget = widgets::Table::new(table_contents)
-            .header(self.get_table_header())
-            .highlight_symbol("> ")
-            .highlight_style(style::Style::default().add_modifier(style::Modifier::HIDDEN | style::Modifier::BOLD))
-            .widths(&column_widths)
-            .column_spacing(2);
+            .header(self.get_table_header(&column_widths, &column_alignments))
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .highlight_style(highlight_style)
+            .widths(&column_constraints)
+            .column_spacing(self.view_model.options.column_spacing);
+        let body_size = if self.view_model.options.totals {
+            let chunks = layout::Layout::default()
+                .direction(layout::Direction::Vertical)
+                .constraints([layout::Constraint::Min(0), layout::Constraint::Length(1)].as_ref())
+                .split(size);
+            // the totals row is drawn as selected to get the same leading column as the body
+            let totals_widget = widgets::Table::new(vec![self.get_totals_row(&column_widths, &column_alignments)])
+                .highlight_symbol(TOTALS_SYMBOL)
+                .widths(&column_constraints)
+                .column_spacing(self.view_model.options.column_spacing);
+            let mut totals_state = widgets::TableState::default();
+            totals_state.select(Some(0));
+            f.render_stateful_widget(totals_widget, chunks[1], &mut totals_state);
+            chunks[0]
+        } else {
+            size
+        };
 
-        f.render_stateful_widget(table_widget, size, &mut self.view_model.table_state);
+        self.view_model.page_height = usize::from(body_size.height.saturating_sub(TABLE_HEADER_HEIGHT)).max(1);
+        self.view_model.area = body_size;
+        self.view_model.update_offset();
+        f.render_stateful_widget(table_widget, body_size, &mut self.view_model.table_state);
+        if hidden_columns > 0 {
+            self.render_hidden_columns(f, body_size, &column_constraints, hidden_columns);
+        }
+    }
+
+    /// Shows the number of columns left out at the end of the header, over the last column if there is no room after it.
+    fn render_hidden_columns<B: backend::Backend>(
+        &self,
+        f: &mut Frame<B>,
+        area: layout::Rect,
+        constraints: &[layout::Constraint],
+        hidden: usize,
+    ) {
+        let spacing = self.view_model.options.column_spacing;
+        let used = constraints
+            .iter()
+            .fold(HIGHLIGHT_SYMBOL.len(), |used, constraint| match constraint {
+                layout::Constraint::Length(width) => used + usize::from(*width + spacing),
+                _ => used,
+            });
+        let free = usize::from(area.width).saturating_sub(used);
+        let marker = if format!("+{hidden} more").len() <= free {
+            format!("+{hidden} more")
+        } else {
+            format!("+{hidden}")
+        };
+        let width = u16::try_from(marker.len()).unwrap_or(u16::MAX).min(area.width);
+        let marker_area = layout::Rect::new(area.right() - width, area.y, width, 1);
+        f.render_widget(widgets::Paragraph::new(marker).style(self.get_header_style()), marker_area);
+    }
+
+    fn get_totals_row<'b>(&self, widths: &[usize], alignments: &[layout::Alignment]) -> widgets::Row<'b> {
+        let mut cells: Vec<widgets::Cell> = Vec::new();
+        if self.view_model.get_line_number_width().is_some() {
+            cells.push(widgets::Cell::from(""));
+        }
+        cells.extend(self.get_column_names(widths).into_iter().enumerate().map(|(j, name)| {
+            let total = get_column_total(self.view_model.df.as_ref(), name, self.view_model.options);
+            widgets::Cell::from(align_text(truncate_text(total, widths[j]), widths[j], alignments[j]))
+        }));
+        widgets::Row::new(cells).style(style::Style::default().add_modifier(style::Modifier::BOLD))
     }
 
-    fn get_table_contents<'b>(&self) -> Vec<widgets::Row<'b>> {
+    fn get_table_contents<'b>(&self, widths: &[usize], alignments: &[layout::Alignment]) -> Vec<widgets::Row<'b>> {
         let mut table_contents: Vec<widgets::Row> = Vec::new();
         let df = &self.view_model.df;
+        let column_names = self.get_column_names(widths);
+        let colorizers: Vec<_> = column_names
+            .iter()
+            .map(|name| colorizer::select(df.column(name), self.view_model.options))
+            .collect();
+        let decimals: Vec<_> = column_names.iter().map(|name| self.view_model.column_layouts[*name].0).collect();
 
+        let line_number_width = self.view_model.get_line_number_width();
         for i in 0..df.len() {
             let mut row_cells = Vec::new();
-            for name in self.get_column_names() {
-                let column = df.column(name);
-                let colorize = colorizer::select(column);
+            if let Some(width) = line_number_width {
+                row_cells.push(widgets::Cell::from(format!("{:>width$}", i + 1)));
+            }
+            for (j, name) in column_names.iter().enumerate() {
+                let colorize = &colorizers[j];
                 let v = &df.get((name, i));
-                row_cells.push(widgets::Cell::from(v.to_string()).style(style::Style::default().fg(colorize(v))));
+                let text = format_aligned_cell(name, v, self.view_model.options, decimals[j]);
+                let text = align_text(truncate_text(text, widths[j]), widths[j], alignments[j]);
+                let text = match &self.view_model.search {
+                    Some(query) => highlight_matches(text, query),
+                    None => text::Spans::from(text),
+                };
+                row_cells.push(widgets::Cell::from(text).style(style::Style::default().fg(colorize(v))));
             }
-            if let Some(t) = &self.view_model.timeline_column {
-                row_cells.push(widgets::Cell::from(t[i].clone()));
+            for chart in self.view_model.get_shown_charts() {
+                row_cells.push(widgets::Cell::from(chart.cells[i].clone()));
             }
-            table_contents.push(widgets::Row::new(row_cells));
+            let row = widgets::Row::new(row_cells);
+            table_contents.push(if self.is_striped() && i % 2 == 1 {
+                row.style(style::Style::default().bg(STRIPE_COLOR))
+            } else {
+                row
+            });
         }
 
         table_contents
     }
 
-    fn get_table_header<'b>(&self) -> widgets::Row<'b> {
-        let cells: Vec<widgets::Cell> = self
-            .get_column_names()
-            .into_iter()
-            .map(|c| widgets::Cell::from(c.clone()))
-            .collect();
-        widgets::Row::new(cells)
-            .style(style::Style::default().fg(style::Color::Yellow).add_modifier(style::Modifier::BOLD))
-            .bottom_margin(1)
+    fn get_table_header<'b>(&self, widths: &[usize], alignments: &[layout::Alignment]) -> widgets::Row<'b> {
+        let mut cells: Vec<widgets::Cell> = Vec::new();
+        if let Some(width) = self.view_model.get_line_number_width() {
+            cells.push(widgets::Cell::from(format!("{:>width$}", "#")));
+        }
+        let selected = self.view_model.get_selected_visible_column();
+        let key_columns = self.view_model.df.key_columns();
+        cells.extend(self.get_column_names(widths).into_iter().enumerate().map(|(j, c)| {
+            let mut style = style::Style::default();
+            if key_columns.contains(c) {
+                style = if self.view_model.options.colors {
+                    style.fg(style::Color::Cyan)
+                } else {
+                    style.add_modifier(style::Modifier::ITALIC)
+                };
+            }
+            if selected == Some(j) {
+                style = style.add_modifier(style::Modifier::UNDERLINED);
+            }
+            widgets::Cell::from(align_text(truncate_text(c.clone(), widths[j]), widths[j], alignments[j])).style(style)
+        }));
+        widgets::Row::new(cells).style(self.get_header_style()).bottom_margin(1)
     }
 
-    fn get_column_widths(&self) -> Vec<layout::Constraint> {
-        let mut contraints: Vec<_> = self
-            .get_column_names()
+    fn get_header_style(&self) -> style::Style {
+        let header_style = if self.view_model.options.colors {
+            style::Style::default().fg(style::Color::Yellow)
+        } else {
+            style::Style::default()
+        };
+        header_style.add_modifier(style::Modifier::BOLD)
+    }
+
+    fn get_column_alignments(&self, widths: &[usize]) -> Vec<layout::Alignment> {
+        self.get_column_names(widths)
             .into_iter()
             .map(|name| {
-                let column = &self.view_model.df.column(name);
-                let lens: Vec<usize> = column.values.iter().map(get_column_value_width).collect();
-                let max_len = lens.iter().fold(name.len(), |a, b| a.max(*b));
-                if max_len < MAX_STRING_WIDTH as usize {
-                    #[allow(clippy::cast_possible_truncation)]
-                    layout::Constraint::Length(max_len as u16)
+                if self.view_model.df.column(name).is_numeric() {
+                    layout::Alignment::Right
                 } else {
-                    layout::Constraint::Min(MAX_STRING_WIDTH)
+                    layout::Alignment::Left
                 }
             })
+            .collect()
+    }
+
+    fn is_striped(&self) -> bool {
+        self.view_model.options.zebra && self.view_model.options.colors
+    }
+
+    fn get_column_constraints(&self, widths: &[usize]) -> Vec<layout::Constraint> {
+        let mut contraints: Vec<_> = self
+            .view_model
+            .get_line_number_width()
+            .iter()
+            .chain(widths)
+            .map(|width| {
+                #[allow(clippy::cast_possible_truncation)]
+                layout::Constraint::Length(*width as u16)
+            })
             .collect();
-        if self.view_model.timeline_column.is_some() {
-            contraints.push(layout::Constraint::Length(TIMELINE_WIDTH));
-        }
+        contraints.extend(
+            self.view_model
+                .get_shown_charts()
+                .map(|chart| layout::Constraint::Length(chart.width)),
+        );
         contraints
     }
 
-    fn get_column_names(&self) -> Vec<&String> {
-        self.view_model.df.column_names()
+    /// Returns names of the visible columns that are shown with the widths.
+    fn get_column_names(&self, widths: &[usize]) -> Vec<&String> {
+        let mut names = self.view_model.get_visible_column_names();
+        names.truncate(widths.len());
+        names
+    }
+}
+
+/// Splits the text into spans with matches of the query highlighted, letters of the text keep their case.
+fn highlight_matches<'b>(text: String, query: &str) -> text::Spans<'b> {
+    let chars: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().collect();
+    if query.is_empty() {
+        return text::Spans::from(text);
     }
+
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let end = i + query.len();
+        if end <= chars.len() && chars[i..end].iter().zip(&query).all(|(a, b)| a.to_lowercase().eq(b.to_lowercase())) {
+            if !plain.is_empty() {
+                spans.push(text::Span::from(std::mem::take(&mut plain)));
+            }
+            let matched: String = chars[i..end].iter().collect();
+            spans.push(text::Span::styled(matched, style::Style::default().add_modifier(style::Modifier::REVERSED)));
+            i = end;
+        } else {
+            plain.push(chars[i]);
+            i += 1;
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(text::Span::from(plain));
+    }
+    text::Spans::from(spans)
 }
 
-fn get_column_value_width(value: &dataframe::ColumnValue) -> usize {
+/// Formats the value of a cell, missing values, booleans and numbers are shown as configured.
+fn format_value(value: &dataframe::ColumnValue, options: DisplayOptions<'_>) -> String {
     match value {
-        dataframe::ColumnValue::Boolean(_) | dataframe::ColumnValue::None => 1,
-        dataframe::ColumnValue::String(s) => s.len(),
-        dataframe::ColumnValue::Integer(_) => 16,
-        dataframe::ColumnValue::DateTime(_) => 12,
+        dataframe::ColumnValue::Boolean(true) => options.true_value.to_string(),
+        dataframe::ColumnValue::Boolean(false) => options.false_value.to_string(),
+        dataframe::ColumnValue::None => options.null_value.to_string(),
+        dataframe::ColumnValue::Integer(n) => separate_thousands(n.to_string(), options.thousands_separator),
+        dataframe::ColumnValue::Float(f) => {
+            let text = match options.decimals {
+                Some(decimals) => format!("{:.decimals$}", f.0),
+                None => f.0.to_string(),
+            };
+            separate_thousands(text, options.thousands_separator)
+        }
+        dataframe::ColumnValue::String(s) => single_line(s),
+        v => v.to_string(),
+    }
+}
+
+/// Replaces line breaks with a glyph and tabs with a space, so that the text of a cell stays on its row.
+fn single_line(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .chars()
+        .map(|c| match c {
+            '\n' | '\r' => NEWLINE_SYMBOL,
+            '\t' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Formats the value of a cell of the column in the display format of the column, if it applies to the value.
+pub fn format_cell(name: &str, value: &dataframe::ColumnValue, options: DisplayOptions<'_>) -> String {
+    let format = options
+        .column_formats
+        .iter()
+        .find(|(column, _)| column == name)
+        .map(|(_, format)| *format);
+    match format.and_then(|format| format.format(value)) {
+        Some(text) => text,
+        None => format_value(value, options),
+    }
+}
+
+/// Most decimal places of aligned floats, floats without a short representation such as `0.1 + 0.2` are rounded to them.
+const MAX_ALIGNED_DECIMALS: usize = 6;
+
+/// Returns the number of decimal places that line up decimal points of the floats of the column, if they are aligned.
+///
+/// Floats have the decimal places of their shortest representation that reads back as the same number, up to a maximum.
+pub fn get_aligned_decimals(column: &dataframe::Column, options: DisplayOptions<'_>) -> Option<usize> {
+    if !options.align_decimals || options.decimals.is_some() || options.column_formats.iter().any(|(name, _)| *name == column.name) {
+        return None;
+    }
+    column
+        .values
+        .iter()
+        .filter_map(|v| match v {
+            dataframe::ColumnValue::Float(f) => Some(f.0.to_string()),
+            _ => None,
+        })
+        .map(|text| text.find('.').map_or(0, |point| text.len() - point - 1))
+        .max()
+        .map(|decimals| decimals.min(MAX_ALIGNED_DECIMALS))
+}
+
+/// Formats the value of a cell with the decimal places of its column, integers get zeros so that they line up with floats.
+pub fn format_aligned_cell(name: &str, value: &dataframe::ColumnValue, options: DisplayOptions<'_>, decimals: Option<usize>) -> String {
+    match (decimals, value) {
+        (Some(decimals), dataframe::ColumnValue::Float(_)) => format_cell(
+            name,
+            value,
+            DisplayOptions {
+                decimals: Some(decimals),
+                ..options
+            },
+        ),
+        (Some(decimals), dataframe::ColumnValue::Integer(_)) if decimals > 0 => {
+            format!("{}.{}", format_cell(name, value, options), "0".repeat(decimals))
+        }
+        _ => format_cell(name, value, options),
+    }
+}
+
+/// Inserts the separator between groups of three digits of the integer part, other text is kept as is.
+fn separate_thousands(number: String, separator: Option<&str>) -> String {
+    let Some(separator) = separator else {
+        return number;
+    };
+    let unsigned = number.strip_prefix('-').unwrap_or(&number);
+    let (integer, fraction) = unsigned.split_at(unsigned.find('.').unwrap_or(unsigned.len()));
+    if integer.is_empty() || !integer.chars().all(|c| c.is_ascii_digit()) {
+        return number;
+    }
+    let mut result = String::from(if unsigned.len() < number.len() { "-" } else { "" });
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push_str(separator);
+        }
+        result.push(digit);
+    }
+    result.push_str(fraction);
+    result
+}
+
+/// Sums numeric columns, counts distinct values of other columns.
+fn get_column_total(df: &dyn DataFrame, name: &String, options: DisplayOptions<'_>) -> String {
+    let values: Vec<&dataframe::ColumnValue> = (0..df.len()).map(|i| df.get((name, i))).collect();
+    if !df.column(name).is_numeric() {
+        let distinct: HashSet<_> = values.into_iter().filter(|v| **v != dataframe::ColumnValue::None).collect();
+        return format!("{} distinct", distinct.len());
+    }
+
+    let total = if values
+        .iter()
+        .all(|v| matches!(v, dataframe::ColumnValue::Integer(_) | dataframe::ColumnValue::None))
+    {
+        let sum = values.iter().filter_map(|v| {
+            if let dataframe::ColumnValue::Integer(n) = v {
+                Some(*n)
+            } else {
+                None
+            }
+        });
+        dataframe::ColumnValue::Integer(sum.fold(0, i64::saturating_add))
+    } else {
+        let sum = dataframe::Float(values.iter().filter_map(|v| v.as_f64()).sum());
+        if values.iter().any(|v| matches!(v, dataframe::ColumnValue::Percent(_))) {
+            dataframe::ColumnValue::Percent(sum)
+        } else {
+            dataframe::ColumnValue::Float(sum)
+        }
+    };
+    format_cell(name, &total, options)
+}
+
+pub fn truncate_text(text: String, width: usize) -> String {
+    if text.chars().count() > width {
+        let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    } else {
+        text
+    }
+}
+
+fn align_text(text: String, width: usize, alignment: layout::Alignment) -> String {
+    match alignment {
+        layout::Alignment::Right => format!("{text:>width$}"),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, DataFrame, Float, MaterializedDataFrame};
+    use crate::io::format::ColumnFormat;
+    use crate::ui::table::{
+        align_text, format_aligned_cell, format_cell, format_value, get_aligned_decimals, get_column_total, highlight_matches,
+        truncate_text, ChartColumn, ViewModel,
+    };
+    use crate::ui::{DisplayOptions, HighlightStyle};
+    use tui::layout;
+    use tui::style;
+
+    #[test]
+    fn move_selected_wraps_around_or_stops_at_edges() {
+        let dataframe = || {
+            let column = Column {
+                name: String::from("a"),
+                values: (0..3).map(ColumnValue::Integer).collect(),
+            };
+            let records = (0..3).map(|_| serde_json::json!({})).collect();
+            MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records)
+        };
+        let mut wrapping = ViewModel::new(Box::new(dataframe()), &[], Vec::new(), DisplayOptions::DEFAULT);
+        wrapping.move_selected(true);
+        assert_eq!(2, wrapping.selected);
+        wrapping.move_selected(false);
+        assert_eq!(0, wrapping.selected);
+
+        let options = DisplayOptions {
+            wrap_around: false,
+            ..DisplayOptions::DEFAULT
+        };
+        let mut clamping = ViewModel::new(Box::new(dataframe()), &[], Vec::new(), options);
+        clamping.move_selected(true);
+        assert_eq!(0, clamping.selected);
+        clamping.jump_to(2);
+        clamping.move_selected(false);
+        assert_eq!(2, clamping.selected);
+    }
+
+    #[test]
+    fn move_selected_column_scrolls_to_keep_it_visible() {
+        let columns: Vec<Column> = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|name| Column {
+                name: String::from(name),
+                values: vec![ColumnValue::Boolean(true)],
+            })
+            .collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), vec![serde_json::json!({})]);
+        let frozen = [String::from("c")];
+        let mut view_model = ViewModel::new(Box::new(df), &frozen, Vec::new(), DisplayOptions::DEFAULT);
+        assert_eq!(vec!["c", "a", "b", "d"], view_model.get_visible_column_names());
+        // every column is one character wide and followed by two spaces of spacing
+        view_model.area = layout::Rect::new(0, 0, 9, 10);
+        assert_eq!(Some((2, 3, 4)), view_model.get_column_range(9));
+
+        view_model.move_selected_column(false);
+        view_model.move_selected_column(false);
+        assert_eq!("b", view_model.get_selected_column_name());
+        assert_eq!(vec!["c", "a", "b", "d"], view_model.get_visible_column_names());
+        view_model.move_selected_column(false);
+        view_model.move_selected_column(false);
+        assert_eq!("d", view_model.get_selected_column_name());
+        assert_eq!(vec!["c", "b", "d"], view_model.get_visible_column_names());
+        assert_eq!(Some((3, 4, 4)), view_model.get_column_range(9));
+
+        view_model.move_selected_column(true);
+        assert_eq!(vec!["c", "b", "d"], view_model.get_visible_column_names());
+        view_model.move_selected_column(true);
+        view_model.move_selected_column(true);
+        assert_eq!("c", view_model.get_selected_column_name());
+        assert_eq!(vec!["c", "a", "b", "d"], view_model.get_visible_column_names());
+        assert_eq!(None, view_model.get_column_range(20));
+
+        // with one space of spacing all four columns fit in the same width
+        view_model.options.column_spacing = 1;
+        assert_eq!(None, view_model.get_column_range(9));
+    }
+
+    #[test]
+    fn get_shown_column_widths_leaves_out_columns_that_dont_fit() {
+        let columns: Vec<Column> = [("a", "x"), ("b", "yyyyyyyy")]
+            .into_iter()
+            .map(|(name, value)| Column {
+                name: String::from(name),
+                values: vec![ColumnValue::String(String::from(value))],
+            })
+            .collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), vec![serde_json::json!({})]);
+        let view_model = ViewModel::new(Box::new(df), &[], Vec::new(), DisplayOptions::DEFAULT);
+        assert_eq!(vec![1, 8], view_model.get_shown_column_widths(13));
+        assert_eq!(vec![1], view_model.get_shown_column_widths(12));
+        assert_eq!(Some((1, 1, 2)), view_model.get_column_range(12));
+        // the first column is narrowed rather than left out
+        assert_eq!(vec![0], view_model.get_shown_column_widths(2));
+    }
+
+    #[test]
+    fn hidden_timeline_gives_its_width_to_the_columns() {
+        let columns: Vec<Column> = [("a", "x"), ("b", "yyyyyyyy")]
+            .into_iter()
+            .map(|(name, value)| Column {
+                name: String::from(name),
+                values: vec![ColumnValue::String(String::from(value))],
+            })
+            .collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), vec![serde_json::json!({})]);
+        let timeline = ChartColumn {
+            cells: vec![String::from("|")],
+            width: 10,
+            timeline: true,
+        };
+        let mut view_model = ViewModel::new(Box::new(df), &[], vec![timeline], DisplayOptions::DEFAULT);
+        assert!(view_model.has_timeline());
+        assert_eq!(vec![1], view_model.get_shown_column_widths(24));
+        view_model.show_timeline = false;
+        assert_eq!(vec![1, 8], view_model.get_shown_column_widths(24));
+    }
+
+    fn numbers_dataframe(count: i64) -> MaterializedDataFrame {
+        let column = Column {
+            name: String::from("n"),
+            values: (0..count).map(ColumnValue::Integer).collect(),
+        };
+        let records = (0..count).map(|n| serde_json::json!({ "n": n })).collect();
+        MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records)
+    }
+
+    #[test]
+    fn column_widths_fit_formatted_numbers() {
+        let df = numbers_dataframe(1200);
+        let view_model = ViewModel::new(Box::new(df), &[], Vec::new(), DisplayOptions::DEFAULT);
+        assert_eq!(vec![4], view_model.get_column_widths());
+        let options = DisplayOptions {
+            thousands_separator: Some(","),
+            ..DisplayOptions::DEFAULT
+        };
+        let view_model = ViewModel::new(Box::new(numbers_dataframe(1200)), &[], Vec::new(), options);
+        assert_eq!(vec![5], view_model.get_column_widths());
+    }
+
+    #[test]
+    fn page_moves_by_visible_rows_and_clamps() {
+        let df = numbers_dataframe(10);
+        let mut view_model = ViewModel::new(Box::new(df), &[], Vec::new(), DisplayOptions::DEFAULT);
+        view_model.page_height = 4;
+        view_model.page(false);
+        assert_eq!(4, view_model.selected);
+        view_model.page(false);
+        view_model.page(false);
+        assert_eq!(9, view_model.selected);
+        view_model.page(true);
+        assert_eq!(5, view_model.selected);
+        view_model.page(true);
+        view_model.page(true);
+        assert_eq!(0, view_model.selected);
+    }
+
+    #[test]
+    fn row_at_accounts_for_header_and_scrolling() {
+        let df = numbers_dataframe(10);
+        let mut view_model = ViewModel::new(Box::new(df), &[], Vec::new(), DisplayOptions::DEFAULT);
+        view_model.area = layout::Rect::new(0, 1, 20, 6);
+        view_model.page_height = 4;
+        assert_eq!(None, view_model.row_at(5, 2));
+        assert_eq!(Some(0), view_model.row_at(5, 3));
+        assert_eq!(None, view_model.row_at(25, 3));
+
+        view_model.jump_to(6);
+        view_model.update_offset();
+        assert_eq!(Some(3), view_model.row_at(5, 3));
+        assert_eq!(Some(6), view_model.row_at(5, 6));
+        assert_eq!(None, view_model.row_at(5, 7));
+    }
+
+    #[test]
+    fn get_column_total_sums_numbers_and_counts_other_values() {
+        let df = numbers_dataframe(10);
+        assert_eq!("45", get_column_total(&df, &String::from("n"), DisplayOptions::DEFAULT));
+
+        let column = Column {
+            name: String::from("s"),
+            values: vec![
+                ColumnValue::String(String::from("a")),
+                ColumnValue::String(String::from("b")),
+                ColumnValue::String(String::from("a")),
+                ColumnValue::None,
+            ],
+        };
+        let records = (0..4).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        assert_eq!("2 distinct", get_column_total(&df, &String::from("s"), DisplayOptions::DEFAULT));
+    }
+
+    #[test]
+    fn get_column_widths_fit_null_placeholder() {
+        let column = Column {
+            name: String::from("s"),
+            values: vec![ColumnValue::String(String::new()), ColumnValue::None],
+        };
+        let records = (0..2).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), records);
+        let options = DisplayOptions {
+            null_value: "null",
+            ..DisplayOptions::DEFAULT
+        };
+        let view_model = ViewModel::new(Box::new(df), &[], Vec::new(), options);
+        assert_eq!(vec![4], view_model.get_column_widths());
+    }
+
+    #[test]
+    fn column_order_puts_listed_columns_first_and_skips_hidden_columns() {
+        let columns: Vec<Column> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| Column {
+                name: String::from(*name),
+                values: vec![ColumnValue::Integer(1)],
+            })
+            .collect();
+        let df = MaterializedDataFrame::new(columns.into_iter().map(|c| (c.name.clone(), c)).collect(), vec![serde_json::json!({})]);
+        let column_order = [String::from("d"), String::from("b")];
+        let frozen = [String::from("c")];
+        let options = DisplayOptions {
+            column_order: &column_order,
+            ..DisplayOptions::DEFAULT
+        };
+        let view_model = ViewModel::new(Box::new(df), &frozen, Vec::new(), options);
+        assert_eq!(vec!["c", "d", "b", "a"], view_model.get_visible_column_names());
+
+        let hidden_columns = [String::from("b"), String::from("c")];
+        let options = DisplayOptions {
+            hidden_columns: &hidden_columns,
+            ..options
+        };
+        let view_model = ViewModel::new(view_model.df, &frozen, Vec::new(), options);
+        assert_eq!(vec!["d", "a"], view_model.get_visible_column_names());
+    }
+
+    #[test]
+    fn highlight_matches_ignores_case_and_keeps_original_text() {
+        let spans = highlight_matches(String::from("GET /Users/users"), "users");
+        let parts: Vec<(&str, bool)> = spans
+            .0
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style.add_modifier.contains(style::Modifier::REVERSED)))
+            .collect();
+        assert_eq!(vec![("GET /", false), ("Users", true), ("/", false), ("users", true)], parts);
+        assert_eq!(1, highlight_matches(String::from("GET"), "post").0.len());
+    }
+
+    #[test]
+    fn search_selects_next_matching_row() {
+        let mut view_model = ViewModel::new(Box::new(numbers_dataframe(30)), &[], Vec::new(), DisplayOptions::DEFAULT);
+        assert!(view_model.search("2"));
+        assert_eq!(2, view_model.selected);
+        assert!(view_model.search("2"));
+        assert_eq!(12, view_model.selected);
+        assert!(!view_model.search("x"));
+        assert_eq!(12, view_model.selected);
+    }
+
+    #[test]
+    fn format_value_uses_configured_bool_glyphs() {
+        let options = DisplayOptions {
+            true_value: "✓",
+            false_value: "✗",
+            ..DisplayOptions::DEFAULT
+        };
+        assert_eq!("✓", format_value(&ColumnValue::Boolean(true), options));
+        assert_eq!("✗", format_value(&ColumnValue::Boolean(false), options));
+        assert_eq!("+", format_value(&ColumnValue::Boolean(true), DisplayOptions::DEFAULT));
+    }
+
+    #[test]
+    fn format_value_keeps_multiline_strings_on_one_line() {
+        let value = ColumnValue::String(String::from("failed:\r\n\tat main\nretrying"));
+        assert_eq!("failed:⏎ at main⏎retrying", format_value(&value, DisplayOptions::DEFAULT));
+        assert_eq!("failed:\r\n\tat main\nretrying", value.to_string());
+    }
+
+    #[test]
+    fn format_cell_humanizes_byte_sizes() {
+        let column_formats = [
+            (String::from("size"), ColumnFormat::Bytes),
+            (String::from("sent"), ColumnFormat::DecimalBytes),
+        ];
+        let options = DisplayOptions {
+            column_formats: &column_formats,
+            ..DisplayOptions::DEFAULT
+        };
+        let format = |name: &str, n: i64| format_cell(name, &ColumnValue::Integer(n), options);
+        assert_eq!("0 B", format("size", 0));
+        assert_eq!("1023 B", format("size", 1023));
+        assert_eq!("1.0 KiB", format("size", 1024));
+        assert_eq!("900 KiB", format("size", 900 * 1024));
+        assert_eq!("1.5 MiB", format("size", 1024 * 1024 * 3 / 2));
+        assert_eq!("-2.0 KiB", format("size", -2048));
+        assert_eq!("1.0 kB", format("sent", 1000));
+        assert_eq!("1024", format("other", 1024));
+        assert_eq!("∅", format_cell("size", &ColumnValue::None, options));
+    }
+
+    #[test]
+    fn aligned_decimals_line_up_decimal_points() {
+        let column = Column {
+            name: String::from("price"),
+            values: vec![
+                ColumnValue::Float(Float(1.5)),
+                ColumnValue::Float(Float(12.25)),
+                ColumnValue::Integer(3),
+            ],
+        };
+        let options = DisplayOptions {
+            align_decimals: true,
+            ..DisplayOptions::DEFAULT
+        };
+        let decimals = get_aligned_decimals(&column, options);
+        assert_eq!(Some(2), decimals);
+        let cells: Vec<String> = column
+            .values
+            .iter()
+            .map(|v| align_text(format_aligned_cell(&column.name, v, options, decimals), 6, layout::Alignment::Right))
+            .collect();
+        assert_eq!(vec!["  1.50", " 12.25", "  3.00"], cells);
+        assert_eq!(None, get_aligned_decimals(&column, DisplayOptions::DEFAULT));
+
+        let column = Column {
+            name: String::from("sum"),
+            values: vec![ColumnValue::Float(Float(0.1 + 0.2)), ColumnValue::Float(Float(1.5))],
+        };
+        assert_eq!(Some(6), get_aligned_decimals(&column, options));
+    }
+
+    #[test]
+    fn highlight_styles_keep_text_visible() {
+        for highlight in [HighlightStyle::Reversed, HighlightStyle::Bold, HighlightStyle::Underlined] {
+            assert!(!highlight.style().add_modifier.contains(style::Modifier::HIDDEN), "{highlight:?} hides text");
+        }
+    }
+
+    #[test]
+    fn format_value_separates_thousands_on_screen_only() {
+        let options = DisplayOptions {
+            thousands_separator: Some(","),
+            decimals: Some(2),
+            ..DisplayOptions::DEFAULT
+        };
+        let value = ColumnValue::Integer(1_234_567);
+        assert_eq!("1,234,567", format_value(&value, options));
+        assert_eq!("1234567", value.to_string());
+        // records copied or printed on exit keep the raw number
+        let column = Column {
+            name: String::from("n"),
+            values: vec![value.clone()],
+        };
+        let df =
+            MaterializedDataFrame::new([(column.name.clone(), column)].into_iter().collect(), vec![serde_json::json!({"n": 1_234_567})]);
+        assert!(df.raw(0).contains("1234567"));
+        assert_eq!("-1,234.50", format_value(&ColumnValue::Float(Float(-1234.5)), options));
+        assert_eq!("999", format_value(&ColumnValue::Integer(999), options));
+        assert_eq!("1234567", format_value(&value, DisplayOptions::DEFAULT));
+    }
+
+    #[test]
+    fn jump_to_clamps_to_last_row() {
+        let df = numbers_dataframe(3);
+        let mut view_model = ViewModel::new(Box::new(df), &[], Vec::new(), DisplayOptions::DEFAULT);
+        view_model.jump_to(usize::MAX);
+        assert_eq!(2, view_model.selected);
+    }
+
+    #[test]
+    fn truncate_text_keeps_short_values() {
+        assert_eq!("hello", truncate_text(String::from("hello"), 5));
+    }
+
+    #[test]
+    fn truncate_text_appends_ellipsis() {
+        assert_eq!("hel…", truncate_text(String::from("hello"), 4));
+    }
+
+    #[test]
+    fn truncate_text_respects_char_boundaries() {
+        assert_eq!("привет…", truncate_text(String::from("приветствую"), 7));
     }
 }
@@ -1,40 +1,30 @@
 use crate::io::dataframe::{Column, ColumnValue, DataFrame, DataFrameGroupView, MaterializedDataFrame};
 use chrono::{DateTime, Utc};
 
-pub fn create_timeline_column<'a>(
+pub fn create_timeline_column(
     source_df: &MaterializedDataFrame,
-    df: &DataFrameGroupView<'a>,
+    df: &DataFrameGroupView<'_>,
     column_name: &str,
     resolution: u16,
 ) -> Vec<String> {
     // create time grid
-    let time_column = match source_df.columns.get(column_name) {
-        None => return vec![String::from(""); df.len()],
-        Some(column) => column,
+    let Some(time_column) = source_df.get_column(column_name) else {
+        return vec![String::new(); df.len()];
     };
     let grid = create_timeline_grid(time_column, resolution);
 
-    // create timelines
+    // create timelines, the column is computed once per grouped view, not on every frame
     (0..df.len())
         .map(|i| {
-            let timestamps: Vec<_> = df
-                .group_indices(i)
-                .iter()
-                .map(|j| time_column[*j].clone())
-                .filter_map(|c| if let ColumnValue::DateTime(ts) = c { Some(ts) } else { None })
-                .collect();
             let mut slots: Vec<usize> = vec![0; resolution.into()];
-            for ts in timestamps {
-                let slot_index = grid
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, t)| **t <= ts)
-                    .map(|(j, _)| j)
-                    .last()
-                    .unwrap_or(0);
-                slots[slot_index] += 1;
+            for j in df.group_indices(i) {
+                if let ColumnValue::DateTime(ts) = &time_column[*j] {
+                    // the grid is sorted, so the slot is the last grid time not after the timestamp
+                    let slot_index = grid.partition_point(|t| t <= ts).saturating_sub(1);
+                    slots[slot_index] += 1;
+                }
             }
-            (0..resolution as usize).map(|j| if slots[j] > 0 { '█' } else { ' ' }).collect()
+            slots.iter().map(|count| if *count > 0 { '█' } else { ' ' }).collect()
         })
         .collect()
 }
@@ -61,3 +51,31 @@ fn create_timeline_grid(time_column: &Column, resolution: u16) -> Vec<DateTime<U
     }
     Vec::new()
 }
+
+#[cfg(test)]
+mod test {
+    use crate::io::dataframe::{Column, ColumnValue, DataFrame, GroupOptions, MaterializedDataFrame};
+    use crate::ui::timeline::create_timeline_column;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn create_timeline_column_marks_slots_of_group_timestamps() {
+        let names = Column {
+            name: String::from("name"),
+            values: ["a", "b", "a", "b"].iter().map(|s| ColumnValue::String(String::from(*s))).collect(),
+        };
+        let times = Column {
+            name: String::from("time"),
+            values: [0, 1, 2, 3]
+                .iter()
+                .map(|h| ColumnValue::DateTime(Utc.ymd(2022, 1, 5).and_hms(*h, 0, 0)))
+                .collect(),
+        };
+        let records = (0..4).map(|_| serde_json::json!({})).collect();
+        let df = MaterializedDataFrame::new([names, times].into_iter().map(|c| (c.name.clone(), c)).collect(), records);
+        let group_columns = vec![String::from("name")];
+        let grouped = df.group_by(&group_columns, &[], &GroupOptions::default());
+        assert_eq!(2, grouped.len());
+        assert_eq!(vec!["█  █  ", " █   █"], create_timeline_column(&df, &grouped, "time", 6));
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::SecondsFormat;
+use tui::backend;
+use tui::layout;
+use tui::text;
+use tui::widgets;
+use tui::Frame;
+
+use crate::io::dataframe::ColumnValue;
+use crate::ui::popup::centered;
+
+pub struct View {
+    title: String,
+    text: String,
+}
+
+impl View {
+    pub fn new(column_name: &str, value: &ColumnValue) -> View {
+        let text = match value {
+            ColumnValue::DateTime(d) => d.to_rfc3339_opts(SecondsFormat::Millis, true),
+            v => v.to_string(),
+        };
+        View {
+            title: format!(" {column_name} "),
+            text,
+        }
+    }
+
+    pub fn render<B: backend::Backend>(self, f: &mut Frame<B>, size: layout::Rect) {
+        // long values are wrapped at any character so that URLs and identifiers keep their width
+        let max_width = usize::from(size.width.saturating_sub(4)).max(1);
+        let text_width = self.text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let inner_width = text_width.max(self.title.chars().count()).clamp(1, max_width);
+        let lines: Vec<text::Spans> = self
+            .text
+            .lines()
+            .flat_map(|line| {
+                let chars: Vec<char> = line.chars().collect();
+                if chars.is_empty() {
+                    vec![text::Spans::default()]
+                } else {
+                    chars
+                        .chunks(inner_width)
+                        .map(|c| text::Spans::from(c.iter().collect::<String>()))
+                        .collect()
+                }
+            })
+            .collect();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let width = ((inner_width + 2) as u16).min(size.width);
+        #[allow(clippy::cast_possible_truncation)]
+        let height = ((lines.len() + 2).min(u16::MAX.into()) as u16).min(size.height);
+        let area = centered(size, width, height);
+        let para = widgets::Paragraph::new(lines).block(widgets::Block::default().title(self.title).borders(widgets::Borders::ALL));
+        f.render_widget(widgets::Clear, area);
+        f.render_widget(para, area);
+    }
+}